@@ -0,0 +1,149 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::bottom_up;
+use super::seg_tree::{Monoid, SegError, SumMonoid};
+
+/// A segment tree storing all of its nodes in a single `Vec`, avoiding the
+/// per-node allocation and `RefCell` bookkeeping of [`SegTree`].
+///
+/// The root lives at index 1 and the children of node `i` live at `2 * i`
+/// and `2 * i + 1`. The number of leaves is padded to a power of two, so
+/// the leaf for index `pos` sits at `size + pos - range.0` and `revise`
+/// and `ask` can walk the tree bottom-up without recursion.
+///
+/// [`SegTree`]: super::seg_tree::SegTree
+pub struct ArraySegTree<M: Monoid = SumMonoid>
+{
+    vals: Vec<M::Item>,
+    size: usize,
+    range: (usize, usize),
+}
+
+impl<M: Monoid> ArraySegTree<M>
+{
+    /// Creates a new array-backed segment tree with the specified range
+    /// `[l, r)`.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::ArraySegTree;
+    /// let seg_tree: ArraySegTree = ArraySegTree::new(0, 10);
+    /// ```
+    pub fn new(l: usize, r: usize) -> ArraySegTree<M>
+    {
+        if l > r
+        {
+            panic!("Invalid range: left bound must not exceed right bound");
+        }
+        ArraySegTree {
+            vals: vec![M::identity(); 2 * (r - l).next_power_of_two()],
+            size: (r - l).next_power_of_two(),
+            range: (l, r),
+        }
+    }
+    /// Creates an array-backed segment tree over `[0, data.len())` holding
+    /// `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn from_slice(data: &[M::Item]) -> ArraySegTree<M>
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        let mut seg_tree = Self::new(0, data.len());
+        let size = seg_tree.size;
+        seg_tree.vals[size..size + data.len()].clone_from_slice(data);
+        for node in (1..size).rev()
+        {
+            seg_tree.pull(node);
+        }
+        seg_tree
+    }
+    /// Updates the value at a specific index in the segment tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: M::Item)
+    {
+        self.try_revise(target_pos, value)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Updates the value at a specific index, returning an error instead
+    /// of panicking if the index is out of range.
+    pub fn try_revise(&mut self, target_pos: usize, value: M::Item) -> Result<(), SegError>
+    {
+        if target_pos < self.range.0 || target_pos >= self.range.1
+        {
+            return Err(SegError::OutOfRange { pos: target_pos });
+        }
+        let node = self.size + target_pos - self.range.0;
+        self.vals[node] = value;
+        bottom_up::pull_path(node, |node| self.pull(node));
+        Ok(())
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        self.try_ask(l, r).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Queries the combined value of `[l, r)`, returning an error instead
+    /// of panicking if the query range is invalid.
+    pub fn try_ask(&self, l: usize, r: usize) -> Result<M::Item, SegError>
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            return Err(SegError::InvalidRange { l, r });
+        }
+        Ok(bottom_up::fold(
+            self.size + l - self.range.0,
+            self.size + r - self.range.0,
+            M::identity,
+            |node| &self.vals[node],
+            M::combine,
+        ))
+    }
+
+    // recomputes a node's value from its children
+    fn pull(&mut self, node: usize)
+    {
+        self.vals[node] = M::combine(&self.vals[2 * node], &self.vals[2 * node + 1]);
+    }
+
+    /// Estimates the memory taken by the tree in bytes, the struct itself
+    /// plus its node array, for comparison with
+    /// [`SegTree::memory_bytes`](super::seg_tree::SegTree::memory_bytes).
+    pub fn memory_bytes(&self) -> usize
+    {
+        core::mem::size_of::<Self>() + self.vals.capacity() * core::mem::size_of::<M::Item>()
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> M::Item
+    {
+        self.vals[1].clone()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
@@ -0,0 +1,328 @@
+use alloc::boxed::Box;
+
+use super::seg_tree::SegError;
+
+/// A segment tree over `i32` supporting range "chmin" updates, which replace
+/// every element `a` of a range with `min(a, x)`, together with range sums.
+///
+/// This is the segment tree beats technique: every node keeps its maximum,
+/// the strict second maximum and how many elements equal the maximum. A
+/// `range_chmin` stops at a node once `x` lies above its second maximum,
+/// where only the maximal elements change and the sum can be fixed up in
+/// O(1). This keeps the amortized cost of each update at O(log^2 n).
+pub struct BeatsSegTree
+{
+    sum: i32,
+    max: i32,
+    // the largest value below `max`, `i32::MIN` if every element is `max`
+    second_max: i32,
+    max_count: usize,
+    range: (usize, usize),
+    mid: usize,
+    l_node: Option<Box<BeatsSegTree>>,
+    r_node: Option<Box<BeatsSegTree>>,
+}
+
+impl BeatsSegTree
+{
+    /// Creates a new beats segment tree over `[l, r)`, every element
+    /// starting as `0`.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return `0`
+    /// for the sum and `i32::MIN` for the maximum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::BeatsSegTree;
+    /// let seg_tree = BeatsSegTree::new(0, 10);
+    /// ```
+    pub fn new(l: usize, r: usize) -> BeatsSegTree
+    {
+        if l > r
+        {
+            panic!("Invalid range: left bound must not exceed right bound");
+        }
+        Self::build(l, r, &|_| 0)
+    }
+    /// Creates a beats segment tree over `[0, data.len())` holding `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::BeatsSegTree;
+    /// let mut seg_tree = BeatsSegTree::from_slice(&[5, 1, 4]);
+    /// assert_eq!(seg_tree.ask(0, 3), 10);
+    /// ```
+    pub fn from_slice(data: &[i32]) -> BeatsSegTree
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, data.len(), &|i| data[i])
+    }
+
+    fn build(l_bound: usize, r_bound: usize, leaf: &impl Fn(usize) -> i32) -> BeatsSegTree
+    {
+        if l_bound == r_bound
+        {
+            let mut node = BeatsSegTree::leaf(l_bound, i32::MIN);
+            node.sum = 0;
+            node.max_count = 0;
+            node.range = (l_bound, l_bound);
+            return node;
+        }
+        if r_bound - l_bound == 1
+        {
+            return BeatsSegTree::leaf(l_bound, leaf(l_bound));
+        }
+        let m = l_bound + (r_bound - l_bound) / 2;
+        let mut node = BeatsSegTree::leaf(l_bound, 0);
+        node.range = (l_bound, r_bound);
+        node.mid = m;
+        node.l_node = Some(Box::new(Self::build(l_bound, m, leaf)));
+        node.r_node = Some(Box::new(Self::build(m, r_bound, leaf)));
+        node.pull();
+        node
+    }
+
+    fn leaf(pos: usize, value: i32) -> BeatsSegTree
+    {
+        BeatsSegTree {
+            sum: value,
+            max: value,
+            second_max: i32::MIN,
+            max_count: 1,
+            range: (pos, pos + 1),
+            mid: pos,
+            l_node: None,
+            r_node: None,
+        }
+    }
+    /// Updates the value at a specific index in the segment tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: i32)
+    {
+        if target_pos < self.range.0 || target_pos >= self.range.1
+        {
+            panic!("{}", SegError::OutOfRange { pos: target_pos });
+        }
+        self.update(target_pos, value);
+    }
+
+    fn update(&mut self, target_pos: usize, value: i32)
+    {
+        if (target_pos, target_pos + 1) == self.range
+        {
+            *self = BeatsSegTree::leaf(target_pos, value);
+            return;
+        }
+        self.push();
+        let child = if target_pos < self.mid
+        {
+            &mut self.l_node
+        }
+        else
+        {
+            &mut self.r_node
+        };
+        if let Some(node) = child
+        {
+            node.update(target_pos, value);
+        }
+        self.pull();
+    }
+    /// Replaces every element `a` in `[l, r)` with `min(a, x)`; an empty
+    /// range leaves the tree unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::BeatsSegTree;
+    /// let mut seg_tree = BeatsSegTree::from_slice(&[5, 1, 4, 8]);
+    /// seg_tree.range_chmin(0, 4, 3);
+    /// assert_eq!(seg_tree.ask(0, 4), 3 + 1 + 3 + 3);
+    /// ```
+    pub fn range_chmin(&mut self, l: usize, r: usize, x: i32)
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid update range");
+        }
+        if l == r
+        {
+            return;
+        }
+        self.chmin(l, r, x);
+    }
+
+    fn chmin(&mut self, l: usize, r: usize, x: i32)
+    {
+        // nothing in this node exceeds `x`
+        if r <= self.range.0 || self.range.1 <= l || self.max <= x
+        {
+            return;
+        }
+        // only the maximal elements change, and all to `x`; a leaf always
+        // stops here, as its `second_max` of `i32::MIN` is only a sentinel
+        if l <= self.range.0 && self.range.1 <= r && (self.is_leaf() || self.second_max < x)
+        {
+            self.apply_chmin(x);
+            return;
+        }
+        self.push();
+        for child in self.l_node.iter_mut().chain(self.r_node.iter_mut())
+        {
+            child.chmin(l, r, x);
+        }
+        self.pull();
+    }
+    /// Queries the sum of the specified range `[l, r)`, pushing pending
+    /// updates down along the way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&mut self, l: usize, r: usize) -> i32
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        self.query(l, r, &|node| node.sum, 0, &|a, b| a + b)
+    }
+    /// Queries the maximum of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::BeatsSegTree;
+    /// let mut seg_tree = BeatsSegTree::from_slice(&[5, 1, 4, 8]);
+    /// seg_tree.range_chmin(2, 4, 6);
+    /// assert_eq!(seg_tree.ask_max(1, 4), 6);
+    /// ```
+    #[must_use]
+    pub fn ask_max(&mut self, l: usize, r: usize) -> i32
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        self.query(l, r, &|node| node.max, i32::MIN, &i32::max)
+    }
+
+    // folds `get` over the nodes covering exactly `[l, r)`
+    fn query(
+        &mut self,
+        l: usize,
+        r: usize,
+        get: &impl Fn(&BeatsSegTree) -> i32,
+        identity: i32,
+        combine: &impl Fn(i32, i32) -> i32,
+    ) -> i32
+    {
+        if r <= self.range.0 || self.range.1 <= l
+        {
+            return identity;
+        }
+        if l <= self.range.0 && self.range.1 <= r
+        {
+            return get(self);
+        }
+        self.push();
+        let mut acc = identity;
+        for child in self.l_node.iter_mut().chain(self.r_node.iter_mut())
+        {
+            acc = combine(acc, child.query(l, r, get, identity, combine));
+        }
+        acc
+    }
+
+    fn is_leaf(&self) -> bool
+    {
+        self.l_node.is_none() && self.r_node.is_none()
+    }
+
+    // lowers every maximal element to `x`, which must lie above `second_max`
+    // unless this is a leaf
+    fn apply_chmin(&mut self, x: i32)
+    {
+        if x >= self.max
+        {
+            return;
+        }
+        // in i64, as `max - x` alone can overflow even when the new sum fits
+        let drop = (i64::from(self.max) - i64::from(x)) * self.max_count as i64;
+        self.sum = (i64::from(self.sum) - drop) as i32;
+        self.max = x;
+    }
+    // hands a pending chmin down: a child whose maximum exceeds ours must
+    // have been capped at our maximum
+    fn push(&mut self)
+    {
+        let max = self.max;
+        for child in self.l_node.iter_mut().chain(self.r_node.iter_mut())
+        {
+            child.apply_chmin(max);
+        }
+    }
+    // recomputes the maximum, second maximum, count and sum from the
+    // children
+    fn pull(&mut self)
+    {
+        let (Some(left), Some(right)) = (&self.l_node, &self.r_node)
+        else
+        {
+            return;
+        };
+        self.sum = left.sum + right.sum;
+        if left.max == right.max
+        {
+            self.max = left.max;
+            self.max_count = left.max_count + right.max_count;
+            self.second_max = left.second_max.max(right.second_max);
+        }
+        else
+        {
+            let (high, low) = if left.max > right.max
+            {
+                (left, right)
+            }
+            else
+            {
+                (right, left)
+            };
+            self.max = high.max;
+            self.max_count = high.max_count;
+            self.second_max = high.second_max.max(low.max);
+        }
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
@@ -0,0 +1,48 @@
+// The bottom-up walks shared by the trees that number their nodes like a
+// binary heap, the root at 1 and the children of node `i` at `2 * i` and
+// `2 * i + 1`: `ArraySegTree`, `SegTreeN` and `FnSegTree`. Each tree only
+// says how to read a node and how to combine two values.
+
+// calls `pull` on every ancestor of `node`, from its parent up to the root
+pub(crate) fn pull_path(mut node: usize, mut pull: impl FnMut(usize))
+{
+    while node > 1
+    {
+        node /= 2;
+        pull(node);
+    }
+}
+
+// combines the values of the leaves `lo..hi`, given as node indices, by
+// climbing from both ends and folding in every node that sticks out of the
+// range one level up
+pub(crate) fn fold<'a, T: 'a>(
+    mut lo: usize,
+    mut hi: usize,
+    identity: impl Fn() -> T,
+    node: impl Fn(usize) -> &'a T,
+    combine: impl Fn(&T, &T) -> T,
+) -> T
+{
+    // the nodes taken from the right end come in from right to left, so they
+    // get their own accumulator and are only joined to the left one at the
+    // end, which keeps a non-commutative `combine` in index order
+    let mut left_val = identity();
+    let mut right_val = identity();
+    while lo < hi
+    {
+        if lo % 2 == 1
+        {
+            left_val = combine(&left_val, node(lo));
+            lo += 1;
+        }
+        if hi % 2 == 1
+        {
+            hi -= 1;
+            right_val = combine(node(hi), &right_val);
+        }
+        lo /= 2;
+        hi /= 2;
+    }
+    combine(&left_val, &right_val)
+}
@@ -0,0 +1,109 @@
+use std::sync::{Arc, RwLock};
+
+use super::array_seg_tree::ArraySegTree;
+use super::seg_tree::{Monoid, SegError, SumMonoid};
+
+/// A segment tree that can be shared between threads.
+///
+/// Cloning a `ConcurrentSegTree` hands out another handle to the same
+/// tree. The nodes live in an [`ArraySegTree`] behind an `RwLock`, so any
+/// number of threads can `ask` in parallel while `revise` takes the write
+/// lock for the length of one bottom-up update.
+pub struct ConcurrentSegTree<M: Monoid = SumMonoid>
+{
+    inner: Arc<RwLock<ArraySegTree<M>>>,
+}
+
+impl<M: Monoid> Clone for ConcurrentSegTree<M>
+{
+    fn clone(&self) -> Self
+    {
+        ConcurrentSegTree {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<M: Monoid> ConcurrentSegTree<M>
+{
+    /// Creates a new shared segment tree with the specified range `[l, r)`.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::ConcurrentSegTree;
+    /// let seg_tree: ConcurrentSegTree = ConcurrentSegTree::new(0, 10);
+    /// ```
+    pub fn new(l: usize, r: usize) -> ConcurrentSegTree<M>
+    {
+        ConcurrentSegTree {
+            inner: Arc::new(RwLock::new(ArraySegTree::new(l, r))),
+        }
+    }
+    /// Creates a shared segment tree over `[0, data.len())` holding `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn from_slice(data: &[M::Item]) -> ConcurrentSegTree<M>
+    {
+        ConcurrentSegTree {
+            inner: Arc::new(RwLock::new(ArraySegTree::from_slice(data))),
+        }
+    }
+    /// Updates the value at a specific index, blocking until no other
+    /// thread holds the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&self, target_pos: usize, value: M::Item)
+    {
+        self.try_revise(target_pos, value)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Updates the value at a specific index, returning an error instead
+    /// of panicking if the index is out of range.
+    pub fn try_revise(&self, target_pos: usize, value: M::Item) -> Result<(), SegError>
+    {
+        self.inner
+            .write()
+            .expect("Tree lock poisoned")
+            .try_revise(target_pos, value)
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        self.try_ask(l, r).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Queries the combined value of `[l, r)`, returning an error instead
+    /// of panicking if the query range is invalid.
+    pub fn try_ask(&self, l: usize, r: usize) -> Result<M::Item, SegError>
+    {
+        self.inner.read().expect("Tree lock poisoned").try_ask(l, r)
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> M::Item
+    {
+        self.inner.read().expect("Tree lock poisoned").get_val()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.inner.read().expect("Tree lock poisoned").get_range()
+    }
+}
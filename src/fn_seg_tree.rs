@@ -0,0 +1,133 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::bottom_up;
+use super::seg_tree::SegError;
+
+/// An array-backed segment tree whose combine operation is a closure chosen
+/// at runtime instead of a [`Monoid`] type.
+///
+/// [`Monoid`] only has associated functions, so a closure cannot be plugged
+/// into [`SegTree`]; this tree stores the identity and the boxed closure
+/// itself and otherwise lays out its nodes like [`ArraySegTree`]. `f` must
+/// be associative with `identity` as its identity element.
+///
+/// [`Monoid`]: super::seg_tree::Monoid
+/// [`SegTree`]: super::seg_tree::SegTree
+/// [`ArraySegTree`]: super::array_seg_tree::ArraySegTree
+pub struct FnSegTree<T = i32>
+{
+    vals: Vec<T>,
+    size: usize,
+    range: (usize, usize),
+    identity: T,
+    combine: Box<dyn Fn(T, T) -> T>,
+}
+
+impl<T: Clone> FnSegTree<T>
+{
+    /// Creates a new segment tree over `[l, r)` combining values with `f`,
+    /// every value starting as `identity`.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::FnSegTree;
+    /// let mut seg_tree = FnSegTree::with_combine(0, 5, i32::MAX, |a, b| a.min(b));
+    /// seg_tree.revise(1, 4);
+    /// seg_tree.revise(3, 2);
+    /// assert_eq!(seg_tree.ask(0, 3), 4);
+    /// assert_eq!(seg_tree.ask(0, 5), 2);
+    /// ```
+    pub fn with_combine(
+        l: usize,
+        r: usize,
+        identity: T,
+        f: impl Fn(T, T) -> T + 'static,
+    ) -> FnSegTree<T>
+    {
+        if l > r
+        {
+            panic!("Invalid range: left bound must not exceed right bound");
+        }
+        FnSegTree {
+            vals: vec![identity.clone(); 2 * (r - l).next_power_of_two()],
+            size: (r - l).next_power_of_two(),
+            range: (l, r),
+            identity,
+            combine: Box::new(f),
+        }
+    }
+    /// Updates the value at a specific index in the segment tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: T)
+    {
+        self.try_revise(target_pos, value)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Updates the value at a specific index, returning an error instead
+    /// of panicking if the index is out of range.
+    pub fn try_revise(&mut self, target_pos: usize, value: T) -> Result<(), SegError>
+    {
+        if target_pos < self.range.0 || target_pos >= self.range.1
+        {
+            return Err(SegError::OutOfRange { pos: target_pos });
+        }
+        let node = self.size + target_pos - self.range.0;
+        self.vals[node] = value;
+        bottom_up::pull_path(node, |node| {
+            self.vals[node] =
+                (self.combine)(self.vals[2 * node].clone(), self.vals[2 * node + 1].clone());
+        });
+        Ok(())
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> T
+    {
+        self.try_ask(l, r).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Queries the combined value of `[l, r)`, returning an error instead
+    /// of panicking if the query range is invalid.
+    pub fn try_ask(&self, l: usize, r: usize) -> Result<T, SegError>
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            return Err(SegError::InvalidRange { l, r });
+        }
+        Ok(bottom_up::fold(
+            self.size + l - self.range.0,
+            self.size + r - self.range.0,
+            || self.identity.clone(),
+            |node| &self.vals[node],
+            |a, b| (self.combine)(a.clone(), b.clone()),
+        ))
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> T
+    {
+        self.vals[1].clone()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
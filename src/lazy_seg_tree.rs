@@ -0,0 +1,645 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+use super::seg_tree::{MinMonoid, Monoid, SegError, SumMonoid};
+
+/// A family of maps acting on the values of a [`Monoid`], used to apply
+/// updates to whole ranges lazily.
+pub trait MapMonoid
+{
+    type M: Monoid;
+    type Map: Clone;
+    /// The map that leaves every value unchanged.
+    fn identity_map() -> Self::Map;
+    /// Applies `f` to `x`, the combined value of `len` consecutive elements.
+    fn mapping(f: &Self::Map, x: &Item<Self>, len: usize) -> Item<Self>;
+    /// Composes two maps so that the result applies `g` first, then `f`.
+    fn composition(f: &Self::Map, g: &Self::Map) -> Self::Map;
+    /// Returns `f` as seen by a range starting `offset` elements after the
+    /// one it was applied to. Only maps that depend on the position, like
+    /// [`ProgressionSum`], need to override this.
+    fn shift(f: &Self::Map, _offset: usize) -> Self::Map
+    {
+        f.clone()
+    }
+}
+
+type Item<A> = <<A as MapMonoid>::M as Monoid>::Item;
+
+/// Adds a constant to every element of a range, over range sums of `i32`.
+pub struct AddSum;
+
+impl MapMonoid for AddSum
+{
+    type M = SumMonoid;
+    type Map = i32;
+    fn identity_map() -> i32
+    {
+        0
+    }
+    fn mapping(f: &i32, x: &i32, len: usize) -> i32
+    {
+        x + f * len as i32
+    }
+    fn composition(f: &i32, g: &i32) -> i32
+    {
+        f + g
+    }
+}
+
+/// Overwrites every element of a range with a value, over range sums of
+/// `i32`. `None` is the identity map.
+pub struct AssignSum;
+
+impl MapMonoid for AssignSum
+{
+    type M = SumMonoid;
+    type Map = Option<i32>;
+    fn identity_map() -> Option<i32>
+    {
+        None
+    }
+    fn mapping(f: &Option<i32>, x: &i32, len: usize) -> i32
+    {
+        f.map_or(*x, |value| value * len as i32)
+    }
+    fn composition(f: &Option<i32>, g: &Option<i32>) -> Option<i32>
+    {
+        f.or(*g)
+    }
+}
+
+/// Adds a constant to every element of a range, over range minimums of
+/// `i32`. Adding to a node shifts its minimum by the same amount,
+/// saturating at the `i32` bounds, so that the `i32::MAX` leaves of a tree
+/// built by `new` absorb a positive add instead of overflowing.
+pub struct AddMin;
+
+impl MapMonoid for AddMin
+{
+    type M = MinMonoid;
+    type Map = i32;
+    fn identity_map() -> i32
+    {
+        0
+    }
+    fn mapping(f: &i32, x: &i32, _len: usize) -> i32
+    {
+        x.saturating_add(*f)
+    }
+    fn composition(f: &i32, g: &i32) -> i32
+    {
+        f + g
+    }
+}
+
+/// Multiplies and adds constants over range sums of `i32`. The map
+/// `(mul, add)` sends every element `x` to `mul * x + add`, so a pending
+/// add is scaled by any multiply applied after it.
+pub struct AffineSum;
+
+impl MapMonoid for AffineSum
+{
+    type M = SumMonoid;
+    type Map = (i32, i32);
+    fn identity_map() -> (i32, i32)
+    {
+        (1, 0)
+    }
+    fn mapping(f: &(i32, i32), x: &i32, len: usize) -> i32
+    {
+        f.0 * x + f.1 * len as i32
+    }
+    fn composition(f: &(i32, i32), g: &(i32, i32)) -> (i32, i32)
+    {
+        (f.0 * g.0, f.0 * g.1 + f.1)
+    }
+}
+
+/// Both assigns and adds over range sums of `i32`. The map
+/// `(assign, add)` first overwrites every element with `assign`, if set,
+/// then adds `add`, so an assign clears any pending add while an add on
+/// top of an assign is kept.
+pub struct AssignAddSum;
+
+impl MapMonoid for AssignAddSum
+{
+    type M = SumMonoid;
+    type Map = (Option<i32>, i32);
+    fn identity_map() -> (Option<i32>, i32)
+    {
+        (None, 0)
+    }
+    fn mapping(f: &(Option<i32>, i32), x: &i32, len: usize) -> i32
+    {
+        f.0.map_or(*x, |value| value * len as i32) + f.1 * len as i32
+    }
+    fn composition(f: &(Option<i32>, i32), g: &(Option<i32>, i32)) -> (Option<i32>, i32)
+    {
+        match f.0
+        {
+            Some(_) => *f,
+            None => (g.0, g.1 + f.1),
+        }
+    }
+}
+
+/// Adds an arithmetic progression to a range, over range sums of `i32`.
+///
+/// The map `(start, step)` adds `start + k * step` to the `k`-th element of
+/// the range it is applied to.
+pub struct ProgressionSum;
+
+impl MapMonoid for ProgressionSum
+{
+    type M = SumMonoid;
+    type Map = (i32, i32);
+    fn identity_map() -> (i32, i32)
+    {
+        (0, 0)
+    }
+    fn mapping(f: &(i32, i32), x: &i32, len: usize) -> i32
+    {
+        // start + (start + step) + ... + (start + (len - 1) * step)
+        x + f.0 * len as i32 + f.1 * (len * len.saturating_sub(1) / 2) as i32
+    }
+    fn composition(f: &(i32, i32), g: &(i32, i32)) -> (i32, i32)
+    {
+        (f.0 + g.0, f.1 + g.1)
+    }
+    fn shift(f: &(i32, i32), offset: usize) -> (i32, i32)
+    {
+        (f.0 + f.1 * offset as i32, f.1)
+    }
+}
+
+/// A segment tree supporting range updates through lazy propagation.
+///
+/// Every node keeps a pending map in `lazy` which has already been applied
+/// to its own `val` but not yet to its children.
+pub struct LazySegTree<A: MapMonoid>
+{
+    val: Item<A>,
+    lazy: A::Map,
+    range: (usize, usize),
+    mid: usize,
+    l_node: Option<Rc<RefCell<LazySegTree<A>>>>,
+    r_node: Option<Rc<RefCell<LazySegTree<A>>>>,
+}
+
+impl<A: MapMonoid> LazySegTree<A>
+{
+    /// Creates a new lazy segment tree with the specified range `[l, r)`.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AddSum;
+    /// let seg_tree = LazySegTree::<AddSum>::new(0, 10);
+    /// ```
+    pub fn new(l: usize, r: usize) -> LazySegTree<A>
+    {
+        if l > r
+        {
+            panic!("Invalid range: left bound must not exceed right bound");
+        }
+        Self::build(l, r, &|_| A::M::identity())
+    }
+    /// Creates a lazy segment tree over `[0, data.len())` holding `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn from_slice(data: &[Item<A>]) -> LazySegTree<A>
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, data.len(), &|i| data[i].clone())
+    }
+
+    fn build(l_bound: usize, r_bound: usize, leaf: &impl Fn(usize) -> Item<A>) -> LazySegTree<A>
+    {
+        if r_bound - l_bound <= 1
+        {
+            return LazySegTree {
+                val: if l_bound == r_bound
+                {
+                    A::M::identity()
+                }
+                else
+                {
+                    leaf(l_bound)
+                },
+                lazy: A::identity_map(),
+                l_node: None,
+                r_node: None,
+                range: (l_bound, r_bound),
+                mid: l_bound,
+            };
+        }
+        let m = l_bound + (r_bound - l_bound) / 2;
+        let left = Self::build(l_bound, m, leaf);
+        let right = Self::build(m, r_bound, leaf);
+        LazySegTree {
+            val: A::M::combine(&left.val, &right.val),
+            lazy: A::identity_map(),
+            l_node: Some(Rc::new(RefCell::new(left))),
+            r_node: Some(Rc::new(RefCell::new(right))),
+            range: (l_bound, r_bound),
+            mid: m,
+        }
+    }
+    /// Updates the value at a specific index in the segment tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: Item<A>)
+    {
+        if target_pos < self.range.0 || target_pos >= self.range.1
+        {
+            panic!("{}", SegError::OutOfRange { pos: target_pos });
+        }
+        self.update(target_pos, value);
+    }
+
+    fn update(&mut self, target_pos: usize, value: Item<A>)
+    {
+        if (target_pos, target_pos + 1) == self.range
+        {
+            self.val = value;
+            return;
+        }
+        self.push();
+        if target_pos < self.mid
+        {
+            if let Some(ref left) = self.l_node
+            {
+                left.borrow_mut().update(target_pos, value);
+            }
+        }
+        else
+        {
+            if let Some(ref right) = self.r_node
+            {
+                right.borrow_mut().update(target_pos, value);
+            }
+        }
+        self.pull();
+    }
+    /// Applies the map `f` to every element in `[l, r)`; an empty range
+    /// leaves the tree unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    pub fn apply_range(&mut self, l: usize, r: usize, f: A::Map)
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid update range");
+        }
+        if l == r
+        {
+            return;
+        }
+        self.apply_map(l, r, &f);
+    }
+
+    fn apply_map(&mut self, l: usize, r: usize, f: &A::Map)
+    {
+        if (l, r) == self.range
+        {
+            self.apply_node(f);
+            return;
+        }
+        self.push();
+        if r <= self.mid
+        {
+            if let Some(ref left) = self.l_node
+            {
+                left.borrow_mut().apply_map(l, r, f);
+            }
+        }
+        else if l >= self.mid
+        {
+            if let Some(ref right) = self.r_node
+            {
+                right.borrow_mut().apply_map(l, r, f);
+            }
+        }
+        else
+        {
+            if let Some(ref left) = self.l_node
+            {
+                left.borrow_mut().apply_map(l, self.mid, f);
+            }
+            if let Some(ref right) = self.r_node
+            {
+                right
+                    .borrow_mut()
+                    .apply_map(self.mid, r, &A::shift(f, self.mid - l));
+            }
+        }
+        self.pull();
+    }
+    /// Queries the combined value of the specified range `[l, r)`,
+    /// pushing pending updates down along the way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&mut self, l: usize, r: usize) -> Item<A>
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        if l == r
+        {
+            return A::M::identity();
+        }
+        self.query(l, r)
+    }
+
+    fn query(&mut self, l: usize, r: usize) -> Item<A>
+    {
+        if (l, r) == self.range
+        {
+            return self.val.clone();
+        }
+        self.push();
+        if r <= self.mid
+        {
+            self.l_node
+                .as_ref()
+                .map_or(A::M::identity(), |left| left.borrow_mut().query(l, r))
+        }
+        else if l >= self.mid
+        {
+            self.r_node
+                .as_ref()
+                .map_or(A::M::identity(), |right| right.borrow_mut().query(l, r))
+        }
+        else
+        {
+            let left_val = self.l_node.as_ref().map_or(A::M::identity(), |left| {
+                left.borrow_mut().query(l, self.mid)
+            });
+            let right_val = self.r_node.as_ref().map_or(A::M::identity(), |right| {
+                right.borrow_mut().query(self.mid, r)
+            });
+            A::M::combine(&left_val, &right_val)
+        }
+    }
+
+    /// Resets every value to the identity and drops all pending updates,
+    /// reusing the existing nodes.
+    pub fn clear(&mut self)
+    {
+        self.val = A::M::identity();
+        self.lazy = A::identity_map();
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow_mut().clear();
+        }
+    }
+
+    // applies `f` to this node's value and queues it for the children
+    fn apply_node(&mut self, f: &A::Map)
+    {
+        self.val = A::mapping(f, &self.val, self.range.1 - self.range.0);
+        self.lazy = A::composition(f, &self.lazy);
+    }
+    // hands the pending map down to both children
+    fn push(&mut self)
+    {
+        if let Some(ref left) = self.l_node
+        {
+            left.borrow_mut().apply_node(&self.lazy);
+        }
+        if let Some(ref right) = self.r_node
+        {
+            right
+                .borrow_mut()
+                .apply_node(&A::shift(&self.lazy, self.mid - self.range.0));
+        }
+        self.lazy = A::identity_map();
+    }
+    // recomputes this node's value from its children
+    fn pull(&mut self)
+    {
+        self.val = A::M::combine(
+            &self
+                .l_node
+                .as_ref()
+                .map_or(A::M::identity(), |left| left.borrow().val.clone()),
+            &self
+                .r_node
+                .as_ref()
+                .map_or(A::M::identity(), |right| right.borrow().val.clone()),
+        );
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> Item<A>
+    {
+        self.val.clone()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
+
+impl LazySegTree<AddSum>
+{
+    /// Adds `delta` to every element in `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AddSum;
+    /// let mut seg_tree = LazySegTree::<AddSum>::new(0, 10);
+    /// seg_tree.range_add(2, 5, 3);
+    /// assert_eq!(seg_tree.ask(0, 10), 9);
+    /// ```
+    pub fn range_add(&mut self, l: usize, r: usize, delta: i32)
+    {
+        self.apply_range(l, r, delta);
+    }
+}
+
+impl LazySegTree<AddMin>
+{
+    /// Adds `delta` to every element in `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AddMin;
+    /// let mut seg_tree = LazySegTree::<AddMin>::from_slice(&[4, 2, 7]);
+    /// seg_tree.range_add(0, 2, 5);
+    /// assert_eq!(seg_tree.ask(0, 3), 7);
+    /// ```
+    pub fn range_add(&mut self, l: usize, r: usize, delta: i32)
+    {
+        self.apply_range(l, r, delta);
+    }
+}
+
+impl LazySegTree<AssignAddSum>
+{
+    /// Sets every element in `[l, r)` to `value`, discarding any add
+    /// still pending on the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    pub fn range_assign(&mut self, l: usize, r: usize, value: i32)
+    {
+        self.apply_range(l, r, (Some(value), 0));
+    }
+    /// Adds `delta` to every element in `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AssignAddSum;
+    /// let mut seg_tree = LazySegTree::<AssignAddSum>::new(0, 10);
+    /// seg_tree.range_assign(0, 10, 2);
+    /// seg_tree.range_add(5, 10, 1);
+    /// assert_eq!(seg_tree.ask(0, 10), 25);
+    /// ```
+    pub fn range_add(&mut self, l: usize, r: usize, delta: i32)
+    {
+        self.apply_range(l, r, (None, delta));
+    }
+}
+
+impl LazySegTree<AffineSum>
+{
+    /// Multiplies every element in `[l, r)` by `factor`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AffineSum;
+    /// let mut seg_tree = LazySegTree::<AffineSum>::from_slice(&[1, 2, 3]);
+    /// seg_tree.range_multiply(0, 3, 2);
+    /// assert_eq!(seg_tree.ask(0, 3), 12);
+    /// ```
+    pub fn range_multiply(&mut self, l: usize, r: usize, factor: i32)
+    {
+        self.range_affine(l, r, factor, 0);
+    }
+    /// Adds `delta` to every element in `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    pub fn range_add(&mut self, l: usize, r: usize, delta: i32)
+    {
+        self.range_affine(l, r, 1, delta);
+    }
+    /// Replaces every element `x` in `[l, r)` with `a * x + b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AffineSum;
+    /// let mut seg_tree = LazySegTree::<AffineSum>::from_slice(&[1, 2, 3]);
+    /// seg_tree.range_affine(0, 2, 3, 1);
+    /// assert_eq!(seg_tree.ask(0, 3), 4 + 7 + 3);
+    /// ```
+    pub fn range_affine(&mut self, l: usize, r: usize, a: i32, b: i32)
+    {
+        self.apply_range(l, r, (a, b));
+    }
+}
+
+impl LazySegTree<AssignSum>
+{
+    /// Sets every element in `[l, r)` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::AssignSum;
+    /// let mut seg_tree = LazySegTree::<AssignSum>::new(0, 10);
+    /// seg_tree.range_assign(0, 10, 5);
+    /// assert_eq!(seg_tree.ask(3, 4), 5);
+    /// ```
+    pub fn range_assign(&mut self, l: usize, r: usize, value: i32)
+    {
+        self.apply_range(l, r, Some(value));
+    }
+}
+
+impl LazySegTree<ProgressionSum>
+{
+    /// Adds `start, start + step, start + 2 * step, ...` to the elements of
+    /// `[l, r)` in order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the update range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::LazySegTree;
+    /// # use seg_tree::lazy_seg_tree::ProgressionSum;
+    /// let mut seg_tree = LazySegTree::<ProgressionSum>::new(0, 10);
+    /// seg_tree.range_add_progression(2, 5, 1, 2);
+    /// assert_eq!(seg_tree.ask(0, 10), 1 + 3 + 5);
+    /// assert_eq!(seg_tree.ask(4, 5), 5);
+    /// ```
+    pub fn range_add_progression(&mut self, l: usize, r: usize, start: i32, step: i32)
+    {
+        self.apply_range(l, r, (start, step));
+    }
+}
@@ -0,0 +1,70 @@
+//! The `seg_tree` module provides an implementation of a segment tree for efficient range queries and updates.
+//!
+//! # Example
+//!
+//! ```
+//! use seg_tree::SegTree;
+//!
+//! fn main() {
+//!     let mut seg_tree: SegTree = SegTree::new(0, 10);
+//!     println!("Build success");
+//!
+//!     for i in 0..10 {
+//!         seg_tree.revise(i, i as i32);
+//!     }
+//!     println!("Revise success");
+//!
+//!     for i in 1..=10 {
+//!         println!("Sum from 0 to {}: {}", i - 1, seg_tree.ask(0, i));
+//!     }
+//!     println!("Ask success");
+//! }
+//! ```
+//!
+//! # `no_std`
+//!
+//! The crate only needs `alloc`. Building without the default `std` feature
+//! makes it `#![no_std]`, which drops [`ConcurrentSegTree`] and
+//! [`SegTree::compressed`] since both rely on `std`.
+//!
+//! # `rayon`
+//!
+//! The `rayon` feature adds [`SegTree::par_from_slice`], which builds large
+//! trees on several threads.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod array_seg_tree;
+pub mod beats_seg_tree;
+mod bottom_up;
+#[cfg(feature = "std")]
+pub mod concurrent_seg_tree;
+pub mod fn_seg_tree;
+pub mod lazy_seg_tree;
+pub mod merge_sort_tree;
+pub mod persistent_seg_tree;
+pub mod seg_tree;
+pub mod seg_tree_2d;
+pub mod seg_tree_n;
+pub mod sparse_seg_tree;
+pub mod threshold_seg_tree;
+pub mod undo_seg_tree;
+
+pub use array_seg_tree::ArraySegTree;
+pub use beats_seg_tree::BeatsSegTree;
+#[cfg(feature = "std")]
+pub use concurrent_seg_tree::ConcurrentSegTree;
+pub use fn_seg_tree::FnSegTree;
+pub use lazy_seg_tree::LazySegTree;
+pub use merge_sort_tree::MergeSortTree;
+pub use persistent_seg_tree::PersistentSegTree;
+pub use seg_tree::{offline_distinct, offline_mode, Monoid, SegError, SegTree};
+pub use seg_tree_2d::SegTree2D;
+pub use seg_tree_n::SegTreeN;
+pub use sparse_seg_tree::SparseSegTree;
+pub use threshold_seg_tree::ThresholdSegTree;
+pub use undo_seg_tree::UndoSegTree;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,134 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::seg_tree::SegError;
+
+/// A segment tree whose nodes keep their elements sorted, answering
+/// "how many values in `[l, r)` are at most `x`" in O(log^2 n).
+///
+/// The tree is immutable once built and takes O(n log n) memory, since
+/// every element appears once per level.
+pub struct MergeSortTree
+{
+    sorted: Vec<i32>,
+    range: (usize, usize),
+    mid: usize,
+    l_node: Option<Box<MergeSortTree>>,
+    r_node: Option<Box<MergeSortTree>>,
+}
+
+impl MergeSortTree
+{
+    /// Creates a merge-sort tree over `[0, data.len())` holding `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::MergeSortTree;
+    /// let tree = MergeSortTree::from_slice(&[5, 1, 4, 2, 3]);
+    /// assert_eq!(tree.count_leq(0, 5, 3), 3);
+    /// ```
+    pub fn from_slice(data: &[i32]) -> MergeSortTree
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, data.len(), data)
+    }
+
+    fn build(l_bound: usize, r_bound: usize, data: &[i32]) -> MergeSortTree
+    {
+        let mid = (l_bound + r_bound) / 2;
+        if r_bound - l_bound == 1
+        {
+            return MergeSortTree {
+                sorted: vec![data[l_bound]],
+                range: (l_bound, r_bound),
+                mid,
+                l_node: None,
+                r_node: None,
+            };
+        }
+        let left = Self::build(l_bound, mid, data);
+        let right = Self::build(mid, r_bound, data);
+        // merge the children's sorted runs
+        let mut sorted = Vec::with_capacity(r_bound - l_bound);
+        let (mut i, mut j) = (0, 0);
+        while i < left.sorted.len() && j < right.sorted.len()
+        {
+            if left.sorted[i] <= right.sorted[j]
+            {
+                sorted.push(left.sorted[i]);
+                i += 1;
+            }
+            else
+            {
+                sorted.push(right.sorted[j]);
+                j += 1;
+            }
+        }
+        sorted.extend_from_slice(&left.sorted[i..]);
+        sorted.extend_from_slice(&right.sorted[j..]);
+        MergeSortTree {
+            sorted,
+            range: (l_bound, r_bound),
+            mid,
+            l_node: Some(Box::new(left)),
+            r_node: Some(Box::new(right)),
+        }
+    }
+    /// Counts the values in `[l, r)` that are less than or equal to `x`,
+    /// which is `0` for an empty range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    pub fn count_leq(&self, l: usize, r: usize, x: i32) -> usize
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        if l == r
+        {
+            return 0;
+        }
+        self.count(l, r, x)
+    }
+
+    fn count(&self, l: usize, r: usize, x: i32) -> usize
+    {
+        if (l, r) == self.range
+        {
+            return self.sorted.partition_point(|&value| value <= x);
+        }
+        let mut count = 0;
+        if l < self.mid
+        {
+            if let Some(left) = &self.l_node
+            {
+                count += left.count(l, r.min(self.mid), x);
+            }
+        }
+        if r > self.mid
+        {
+            if let Some(right) = &self.r_node
+            {
+                count += right.count(l.max(self.mid), r, x);
+            }
+        }
+        count
+    }
+
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
@@ -0,0 +1,271 @@
+use alloc::rc::Rc;
+
+use super::seg_tree::{Monoid, SegError, SumMonoid};
+
+/// An immutable segment tree whose updates return a new version.
+///
+/// `revise` copies only the nodes on the path to the updated leaf and
+/// shares every other subtree with the previous version through `Rc`, so
+/// old versions stay valid and queryable.
+pub struct PersistentSegTree<M: Monoid = SumMonoid>
+{
+    val: M::Item,
+    range: (usize, usize),
+    mid: usize,
+    l_node: Option<Rc<PersistentSegTree<M>>>,
+    r_node: Option<Rc<PersistentSegTree<M>>>,
+}
+
+/// A cheap handle to one version of a [`PersistentSegTree`], taken by
+/// [`PersistentSegTree::snapshot`].
+pub struct Version<M: Monoid = SumMonoid>
+{
+    root: PersistentSegTree<M>,
+}
+
+impl<M: Monoid> Clone for Version<M>
+{
+    fn clone(&self) -> Version<M>
+    {
+        Version {
+            root: self.root.share(),
+        }
+    }
+}
+
+impl<M: Monoid> PersistentSegTree<M>
+{
+    /// Creates a new persistent segment tree with the specified range
+    /// `[l, r)`.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::PersistentSegTree;
+    /// let seg_tree: PersistentSegTree = PersistentSegTree::new(0, 10);
+    /// ```
+    pub fn new(l: usize, r: usize) -> PersistentSegTree<M>
+    {
+        if l > r
+        {
+            panic!("Invalid range: left bound must not exceed right bound");
+        }
+        Self::build(l, r, &|_| M::identity())
+    }
+    /// Creates a persistent segment tree over `[0, data.len())` holding
+    /// `data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn from_slice(data: &[M::Item]) -> PersistentSegTree<M>
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, data.len(), &|i| data[i].clone())
+    }
+
+    fn build(
+        l_bound: usize,
+        r_bound: usize,
+        leaf: &impl Fn(usize) -> M::Item,
+    ) -> PersistentSegTree<M>
+    {
+        if r_bound - l_bound <= 1
+        {
+            return PersistentSegTree {
+                val: if l_bound == r_bound
+                {
+                    M::identity()
+                }
+                else
+                {
+                    leaf(l_bound)
+                },
+                l_node: None,
+                r_node: None,
+                range: (l_bound, r_bound),
+                mid: l_bound,
+            };
+        }
+        let m = l_bound + (r_bound - l_bound) / 2;
+        let left = Self::build(l_bound, m, leaf);
+        let right = Self::build(m, r_bound, leaf);
+        PersistentSegTree {
+            val: M::combine(&left.val, &right.val),
+            l_node: Some(Rc::new(left)),
+            r_node: Some(Rc::new(right)),
+            range: (l_bound, r_bound),
+            mid: m,
+        }
+    }
+    /// Returns a new version of the tree with the value at `target_pos`
+    /// replaced, leaving `self` untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::PersistentSegTree;
+    /// let v0: PersistentSegTree = PersistentSegTree::new(0, 10);
+    /// let v1 = v0.revise(2, 10);
+    /// assert_eq!(v0.ask(0, 10), 0);
+    /// assert_eq!(v1.ask(0, 10), 10);
+    /// ```
+    pub fn revise(&self, target_pos: usize, value: M::Item) -> PersistentSegTree<M>
+    {
+        if target_pos < self.range.0 || target_pos >= self.range.1
+        {
+            panic!("{}", SegError::OutOfRange { pos: target_pos });
+        }
+        self.update(target_pos, value)
+    }
+
+    fn update(&self, target_pos: usize, value: M::Item) -> PersistentSegTree<M>
+    {
+        if (target_pos, target_pos + 1) == self.range
+        {
+            return PersistentSegTree {
+                val: value,
+                l_node: None,
+                r_node: None,
+                range: self.range,
+                mid: self.mid,
+            };
+        }
+        let mut l_node = self.l_node.clone();
+        let mut r_node = self.r_node.clone();
+        if target_pos < self.mid
+        {
+            l_node = l_node.map(|left| Rc::new(left.update(target_pos, value)));
+        }
+        else
+        {
+            r_node = r_node.map(|right| Rc::new(right.update(target_pos, value)));
+        }
+        PersistentSegTree {
+            val: M::combine(
+                &l_node
+                    .as_ref()
+                    .map_or(M::identity(), |left| left.val.clone()),
+                &r_node
+                    .as_ref()
+                    .map_or(M::identity(), |right| right.val.clone()),
+            ),
+            l_node,
+            r_node,
+            range: self.range,
+            mid: self.mid,
+        }
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        if l == r
+        {
+            return M::identity();
+        }
+        self.query(l, r)
+    }
+
+    fn query(&self, l: usize, r: usize) -> M::Item
+    {
+        if (l, r) == self.range
+        {
+            self.val.clone()
+        }
+        else if r <= self.mid
+        {
+            self.l_node
+                .as_ref()
+                .map_or(M::identity(), |left| left.query(l, r))
+        }
+        else if l >= self.mid
+        {
+            self.r_node
+                .as_ref()
+                .map_or(M::identity(), |right| right.query(l, r))
+        }
+        else
+        {
+            let left_val = self
+                .l_node
+                .as_ref()
+                .map_or(M::identity(), |left| left.query(l, self.mid));
+            let right_val = self
+                .r_node
+                .as_ref()
+                .map_or(M::identity(), |right| right.query(self.mid, r));
+            M::combine(&left_val, &right_val)
+        }
+    }
+
+    /// Returns a handle to the current version in O(1), sharing every node
+    /// with `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::PersistentSegTree;
+    /// let mut seg_tree: PersistentSegTree = PersistentSegTree::new(0, 10);
+    /// let saved = seg_tree.snapshot();
+    /// seg_tree = seg_tree.revise(2, 10);
+    /// seg_tree.restore(saved);
+    /// assert_eq!(seg_tree.ask(0, 10), 0);
+    /// ```
+    pub fn snapshot(&self) -> Version<M>
+    {
+        Version { root: self.share() }
+    }
+    /// Rolls the tree back to the version `version` was taken from, in
+    /// O(1).
+    pub fn restore(&mut self, version: Version<M>)
+    {
+        *self = version.root;
+    }
+
+    // copies the root, sharing both children
+    fn share(&self) -> PersistentSegTree<M>
+    {
+        PersistentSegTree {
+            val: self.val.clone(),
+            range: self.range,
+            mid: self.mid,
+            l_node: self.l_node.clone(),
+            r_node: self.r_node.clone(),
+        }
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> M::Item
+    {
+        self.val.clone()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
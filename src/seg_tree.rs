@@ -0,0 +1,3437 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ops::Index;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use num_traits::{Num, ToPrimitive};
+
+/// Errors returned by the fallible `try_*` methods of [`SegTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegError
+{
+    /// The index `pos` lies outside the tree's range.
+    OutOfRange
+    {
+        pos: usize
+    },
+    /// The query range `[l, r)` is reversed or not contained in the tree's
+    /// range.
+    InvalidRange
+    {
+        l: usize, r: usize
+    },
+    /// A node was already borrowed, e.g. from a callback re-entering the
+    /// tree it was called from.
+    Concurrent,
+    /// The sum over `[l, r)` does not fit in the item type.
+    Overflow
+    {
+        l: usize, r: usize
+    },
+    /// A tree was to be built from no data at all.
+    Empty,
+    /// A byte buffer passed to `from_bytes` is truncated or malformed.
+    InvalidBytes,
+}
+
+impl fmt::Display for SegError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            SegError::OutOfRange { pos } => write!(f, "Target index out of range: {}", pos),
+            SegError::InvalidRange { l, r } => write!(f, "Invalid query range: [{}, {})", l, r),
+            SegError::Concurrent => write!(f, "Tree node is already borrowed"),
+            SegError::Overflow { l, r } => write!(f, "Sum over [{}, {}) overflows", l, r),
+            SegError::Empty => write!(f, "Cannot build a tree from empty data"),
+            SegError::InvalidBytes => write!(f, "Malformed segment tree bytes"),
+        }
+    }
+}
+
+impl core::error::Error for SegError {}
+
+/// An associative operation with an identity element, used to combine
+/// the values stored in a segment tree.
+pub trait Monoid
+{
+    type Item: Clone;
+    /// The identity element, such that `combine(identity, x) == x`.
+    fn identity() -> Self::Item;
+    /// Combines two values, `a` covering the range left of `b`.
+    fn combine(a: &Self::Item, b: &Self::Item) -> Self::Item;
+}
+
+/// The sum monoid, which makes `ask` return the sum over a range of any
+/// numeric type such as `i64`, `u64` or `f64`.
+pub struct SumMonoid<T = i32>(PhantomData<T>);
+
+impl<T> Monoid for SumMonoid<T>
+where
+    T: Num + Copy,
+{
+    type Item = T;
+    fn identity() -> T
+    {
+        T::zero()
+    }
+    fn combine(a: &T, b: &T) -> T
+    {
+        *a + *b
+    }
+}
+
+/// The sum monoid over `i32` that clamps to `i32::MIN` / `i32::MAX`
+/// instead of overflowing.
+///
+/// Clamping is not associative once a bound is hit, so with mixed signs a
+/// saturated sum depends on how the range splits into nodes.
+pub struct SaturatingSumMonoid;
+
+impl Monoid for SaturatingSumMonoid
+{
+    type Item = i32;
+    fn identity() -> i32
+    {
+        0
+    }
+    fn combine(a: &i32, b: &i32) -> i32
+    {
+        a.saturating_add(*b)
+    }
+}
+
+/// The sum monoid over `i32` that wraps around on overflow, i.e. sums
+/// modulo `2^32`.
+pub struct WrappingSumMonoid;
+
+impl Monoid for WrappingSumMonoid
+{
+    type Item = i32;
+    fn identity() -> i32
+    {
+        0
+    }
+    fn combine(a: &i32, b: &i32) -> i32
+    {
+        a.wrapping_add(*b)
+    }
+}
+
+/// The sum monoid over `i32` that records overflow: `None` marks a sum
+/// that did not fit, and stays `None` when combined further.
+pub struct CheckedSumMonoid;
+
+impl Monoid for CheckedSumMonoid
+{
+    type Item = Option<i32>;
+    fn identity() -> Option<i32>
+    {
+        Some(0)
+    }
+    fn combine(a: &Option<i32>, b: &Option<i32>) -> Option<i32>
+    {
+        a.zip(*b).and_then(|(a, b)| a.checked_add(b))
+    }
+}
+
+/// Polynomial rolling hashes of strings modulo `MOD`, with base `BASE`.
+///
+/// The item `(hash, power)` of a range `c_0 .. c_k` holds
+/// `c_0 * BASE^k + ... + c_k` and `BASE^(k + 1)`, both reduced modulo `MOD`,
+/// so that joining a range on the right takes `hash * power + right_hash`.
+/// Equal substrings always hash equally; different ones collide with
+/// probability about `len / MOD`.
+pub struct PolyHashMonoid<const BASE: u64, const MOD: u64>;
+
+impl<const BASE: u64, const MOD: u64> PolyHashMonoid<BASE, MOD>
+{
+    /// Returns the item of a single character with code `code`.
+    pub fn leaf(code: u64) -> (u64, u64)
+    {
+        (code % MOD, BASE % MOD)
+    }
+}
+
+impl<const BASE: u64, const MOD: u64> Monoid for PolyHashMonoid<BASE, MOD>
+{
+    type Item = (u64, u64);
+    fn identity() -> (u64, u64)
+    {
+        (0, 1 % MOD)
+    }
+    fn combine(a: &(u64, u64), b: &(u64, u64)) -> (u64, u64)
+    {
+        // widened so that products of two residues cannot overflow
+        let mul = |x: u64, y: u64| (x as u128 * y as u128 % MOD as u128) as u64;
+        ((mul(a.0, b.1) + b.0) % MOD, mul(a.1, b.1))
+    }
+}
+
+/// A 2x2 matrix of `i64`, the item type of [`Matrix2Monoid`].
+pub type Matrix2 = [[i64; 2]; 2];
+
+/// Matrix multiplication over [`Matrix2`], with the identity matrix as
+/// identity.
+///
+/// The product is not commutative, so `ask(l, r)` is the product of the
+/// matrices in index order, as used to compose linear recurrences such as
+/// Fibonacci's `[[1, 1], [1, 0]]`.
+pub struct Matrix2Monoid;
+
+impl Monoid for Matrix2Monoid
+{
+    type Item = Matrix2;
+    fn identity() -> Matrix2
+    {
+        [[1, 0], [0, 1]]
+    }
+    fn combine(a: &Matrix2, b: &Matrix2) -> Matrix2
+    {
+        let mut product = [[0; 2]; 2];
+        for (i, row) in product.iter_mut().enumerate()
+        {
+            for (j, cell) in row.iter_mut().enumerate()
+            {
+                *cell = a[i][0] * b[0][j] + a[i][1] * b[1][j];
+            }
+        }
+        product
+    }
+}
+
+/// The minimum monoid over `i32`, with identity `i32::MAX`.
+pub struct MinMonoid;
+
+impl Monoid for MinMonoid
+{
+    type Item = i32;
+    fn identity() -> i32
+    {
+        i32::MAX
+    }
+    fn combine(a: &i32, b: &i32) -> i32
+    {
+        *a.min(b)
+    }
+}
+
+/// The maximum monoid over `i32`, with identity `i32::MIN`.
+pub struct MaxMonoid;
+
+impl Monoid for MaxMonoid
+{
+    type Item = i32;
+    fn identity() -> i32
+    {
+        i32::MIN
+    }
+    fn combine(a: &i32, b: &i32) -> i32
+    {
+        *a.max(b)
+    }
+}
+
+/// The greatest common divisor monoid over `u64`, with identity `0` since
+/// `gcd(0, x) == x`.
+pub struct GcdMonoid;
+
+impl Monoid for GcdMonoid
+{
+    type Item = u64;
+    fn identity() -> u64
+    {
+        0
+    }
+    fn combine(a: &u64, b: &u64) -> u64
+    {
+        gcd(*a, *b)
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64
+{
+    while b != 0
+    {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The bitwise OR monoid over `u64`, with identity `0`.
+pub struct OrMonoid;
+
+impl Monoid for OrMonoid
+{
+    type Item = u64;
+    fn identity() -> u64
+    {
+        0
+    }
+    fn combine(a: &u64, b: &u64) -> u64
+    {
+        *a | *b
+    }
+}
+
+/// The bitwise AND monoid over `u64`, with identity `!0`.
+pub struct AndMonoid;
+
+impl Monoid for AndMonoid
+{
+    type Item = u64;
+    fn identity() -> u64
+    {
+        !0
+    }
+    fn combine(a: &u64, b: &u64) -> u64
+    {
+        *a & *b
+    }
+}
+
+/// The bitwise XOR monoid over `u64`, with identity `0`.
+pub struct XorMonoid;
+
+impl Monoid for XorMonoid
+{
+    type Item = u64;
+    fn identity() -> u64
+    {
+        0
+    }
+    fn combine(a: &u64, b: &u64) -> u64
+    {
+        *a ^ *b
+    }
+}
+
+/// The maximum monoid over `(value, index)` pairs, so that `ask` also
+/// reports where the maximum is. Ties go to the smaller index.
+pub struct MaxIndexMonoid;
+
+impl Monoid for MaxIndexMonoid
+{
+    type Item = (i32, usize);
+    fn identity() -> (i32, usize)
+    {
+        (i32::MIN, usize::MAX)
+    }
+    fn combine(a: &(i32, usize), b: &(i32, usize)) -> (i32, usize)
+    {
+        if b.0 > a.0 || (b.0 == a.0 && b.1 < a.1)
+        {
+            *b
+        }
+        else
+        {
+            *a
+        }
+    }
+}
+
+/// Keeps the two largest values of a range as `(max, second_max)`, with
+/// `i32::MIN` standing in for a missing second value.
+///
+/// The two values come from distinct positions but need not differ, so a
+/// range holding `8` twice reports `(8, 8)`.
+pub struct SecondMaxMonoid;
+
+impl Monoid for SecondMaxMonoid
+{
+    type Item = (i32, i32);
+    fn identity() -> (i32, i32)
+    {
+        (i32::MIN, i32::MIN)
+    }
+    fn combine(a: &(i32, i32), b: &(i32, i32)) -> (i32, i32)
+    {
+        if a.0 >= b.0
+        {
+            (a.0, a.1.max(b.0))
+        }
+        else
+        {
+            (b.0, b.1.max(a.0))
+        }
+    }
+}
+
+/// Tracks `(sum, sum of squares)` over `i64` values, which is enough to
+/// compute the variance of a range.
+///
+/// A leaf holding `v` stores `(v, v * v)`; see [`VarianceMonoid::leaf`].
+pub struct VarianceMonoid;
+
+impl VarianceMonoid
+{
+    /// Returns the item stored at a leaf holding `value`.
+    pub fn leaf(value: i64) -> (i64, i64)
+    {
+        (value, value * value)
+    }
+}
+
+impl Monoid for VarianceMonoid
+{
+    type Item = (i64, i64);
+    fn identity() -> (i64, i64)
+    {
+        (0, 0)
+    }
+    fn combine(a: &(i64, i64), b: &(i64, i64)) -> (i64, i64)
+    {
+        (a.0 + b.0, a.1 + b.1)
+    }
+}
+
+/// The summary of a range kept by [`MaxSubarrayMonoid`].
+///
+/// `prefix`, `suffix` and `best` are the largest sums of a non-empty
+/// subarray touching the left end, touching the right end, and anywhere
+/// in the range. The identity holds `i32::MIN` in all three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subarray
+{
+    pub total: i32,
+    pub best: i32,
+    pub prefix: i32,
+    pub suffix: i32,
+}
+
+impl Subarray
+{
+    /// Returns the summary of a single element `value`.
+    pub fn leaf(value: i32) -> Subarray
+    {
+        Subarray {
+            total: value,
+            best: value,
+            prefix: value,
+            suffix: value,
+        }
+    }
+}
+
+/// Answers maximum subarray sum queries (GSS1) over `i32` values.
+pub struct MaxSubarrayMonoid;
+
+impl Monoid for MaxSubarrayMonoid
+{
+    type Item = Subarray;
+    fn identity() -> Subarray
+    {
+        Subarray {
+            total: 0,
+            best: i32::MIN,
+            prefix: i32::MIN,
+            suffix: i32::MIN,
+        }
+    }
+    fn combine(a: &Subarray, b: &Subarray) -> Subarray
+    {
+        // saturating so that the identity's i32::MIN never wraps around
+        Subarray {
+            total: a.total + b.total,
+            best: a.best.max(b.best).max(a.suffix.saturating_add(b.prefix)),
+            prefix: a.prefix.max(a.total.saturating_add(b.prefix)),
+            suffix: b.suffix.max(b.total.saturating_add(a.suffix)),
+        }
+    }
+}
+
+/// An integer kept reduced modulo `MOD`, the item type of
+/// [`ModSumMonoid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModInt<const MOD: u64>(u64);
+
+impl<const MOD: u64> ModInt<MOD>
+{
+    /// Reduces `value` modulo `MOD`.
+    pub fn new(value: u64) -> ModInt<MOD>
+    {
+        ModInt(value % MOD)
+    }
+    /// Returns the residue, always in `[0, MOD)`.
+    pub fn value(self) -> u64
+    {
+        self.0
+    }
+}
+
+/// The sum monoid modulo `MOD`, e.g. `ModSumMonoid<1_000_000_007>`.
+///
+/// Since the modulus is part of the type, every node of a tree is
+/// guaranteed to reduce by the same one.
+pub struct ModSumMonoid<const MOD: u64>;
+
+impl<const MOD: u64> Monoid for ModSumMonoid<MOD>
+{
+    type Item = ModInt<MOD>;
+    fn identity() -> ModInt<MOD>
+    {
+        ModInt(0)
+    }
+    fn combine(a: &ModInt<MOD>, b: &ModInt<MOD>) -> ModInt<MOD>
+    {
+        // both residues are below MOD, so this never overflows
+        if a.0 >= MOD - b.0
+        {
+            ModInt(a.0 - (MOD - b.0))
+        }
+        else
+        {
+            ModInt(a.0 + b.0)
+        }
+    }
+}
+
+pub struct SegTree<M: Monoid = SumMonoid>
+{
+    val: M::Item,
+    range: (usize, usize),
+    mid: usize,
+    l_node: Option<Rc<RefCell<SegTree<M>>>>,
+    r_node: Option<Rc<RefCell<SegTree<M>>>>,
+}
+
+// below this many leaves `par_build` builds serially, as forking would cost
+// more than it saves
+#[cfg(feature = "rayon")]
+const PARALLEL_BUILD_THRESHOLD: usize = 1 << 14;
+
+// the panic of `new` and `new_with` on a reversed range
+const REVERSED_RANGE: &str = "Invalid range: left bound must not exceed right bound";
+
+// a freshly built subtree handed back from a rayon worker
+#[cfg(feature = "rayon")]
+struct SendTree<M: Monoid>(SegTree<M>);
+
+// SAFETY: every `Rc` in a freshly built subtree has a strong count of one and
+// is only reachable through the wrapped root, so moving the whole subtree to
+// another thread can never touch a reference count from two threads at once.
+#[cfg(feature = "rayon")]
+unsafe impl<M: Monoid> Send for SendTree<M> where M::Item: Send {}
+
+impl<M: Monoid> SegTree<M>
+{
+    /// Creates a new segment tree with the specified range `[l, r)`.
+    ///
+    /// `l == r` gives an empty tree, whose `total` is the identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 10);
+    /// ```
+    pub fn new(l: usize, r: usize) -> SegTree<M>
+    {
+        Self::try_new(l, r).expect(REVERSED_RANGE)
+    }
+    /// Creates a new segment tree with the specified range `[l, r)`, like
+    /// `new`, but returns an error instead of panicking if `l > r`.
+    ///
+    /// Only `l > r` is an error: `l == r` is accepted and gives an empty
+    /// tree, as with `new`, since an empty range is a valid base case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::{SegError, SegTree};
+    /// let seg_tree: Result<SegTree, _> = SegTree::try_new(10, 0);
+    /// assert!(matches!(seg_tree, Err(SegError::InvalidRange { l: 10, r: 0 })));
+    /// let empty: Result<SegTree, _> = SegTree::try_new(3, 3);
+    /// assert!(empty.is_ok());
+    /// ```
+    pub fn try_new(l: usize, r: usize) -> Result<SegTree<M>, SegError>
+    {
+        Self::try_new_with(l, r, M::identity())
+    }
+    /// Creates a new segment tree over `[l, r)` with every leaf set to
+    /// `value`.
+    ///
+    /// The internal nodes are combined while the tree is built, so this
+    /// costs the same as `new`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new_with(0, 5, 2);
+    /// assert_eq!(seg_tree.total(), 10);
+    /// ```
+    pub fn new_with(l: usize, r: usize, value: M::Item) -> SegTree<M>
+    {
+        Self::try_new_with(l, r, value).expect(REVERSED_RANGE)
+    }
+
+    // the one bound check behind `new`, `try_new` and `new_with`
+    fn try_new_with(l: usize, r: usize, value: M::Item) -> Result<SegTree<M>, SegError>
+    {
+        if l > r
+        {
+            return Err(SegError::InvalidRange { l, r });
+        }
+        Ok(Self::build(l, r, &|_| value.clone()))
+    }
+    /// Creates a segment tree over `[0, data.len())` holding `data`,
+    /// built in O(n) without repeated calls to `revise`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// assert_eq!(seg_tree.ask(0, 3), 6);
+    /// ```
+    pub fn from_slice(data: &[M::Item]) -> SegTree<M>
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, data.len(), &|i| data[i].clone())
+    }
+    /// Creates a segment tree over `[0, data.len())` holding `data`, like
+    /// `from_slice`, but returns an error instead of panicking if `data` is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::{SegError, SegTree};
+    /// let seg_tree: Result<SegTree, _> = SegTree::try_from_slice(&[]);
+    /// assert!(matches!(seg_tree, Err(SegError::Empty)));
+    /// ```
+    pub fn try_from_slice(data: &[M::Item]) -> Result<SegTree<M>, SegError>
+    {
+        if data.is_empty()
+        {
+            return Err(SegError::Empty);
+        }
+        Ok(Self::build(0, data.len(), &|i| data[i].clone()))
+    }
+    /// Like `from_slice`, but builds the two halves of every large enough
+    /// node in parallel through `rayon::join`. The resulting tree is the
+    /// same as the one `from_slice` builds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let data: Vec<i32> = (0..100_000).map(|i| i % 7).collect();
+    /// let seg_tree: SegTree = SegTree::par_from_slice(&data);
+    /// assert_eq!(seg_tree.ask(0, 7), 21);
+    /// ```
+    #[cfg(feature = "rayon")]
+    pub fn par_from_slice(data: &[M::Item]) -> SegTree<M>
+    where
+        M::Item: Send + Sync,
+    {
+        if data.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::par_build(0, data.len(), data)
+    }
+    /// Compresses `keys` to the indices `0..k`, where `k` is the number of
+    /// distinct keys, and creates an empty tree over `[0, k)`.
+    ///
+    /// The returned map sends each key to its index, so that `revise` and
+    /// `ask` can be driven by the original keys. Smaller keys get smaller
+    /// indices. Empty `keys` give an empty tree over `[0, 0)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let (mut seg_tree, index): (SegTree, _) = SegTree::compressed(&[1000, 5, 1000, 77]);
+    /// seg_tree.revise(index[&1000], 3);
+    /// assert_eq!(seg_tree.len(), 3);
+    /// assert_eq!(seg_tree.ask(index[&77], index[&1000] + 1), 3);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn compressed(keys: &[i64]) -> (SegTree<M>, HashMap<i64, usize>)
+    {
+        let mut sorted = keys.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        let index = sorted
+            .iter()
+            .enumerate()
+            .map(|(i, &key)| (key, i))
+            .collect();
+        (Self::new(0, sorted.len()), index)
+    }
+
+    // builds the node covering [l_bound, r_bound), taking leaf values from `leaf`
+    fn build(l_bound: usize, r_bound: usize, leaf: &impl Fn(usize) -> M::Item) -> SegTree<M>
+    {
+        if r_bound - l_bound <= 1
+        {
+            return SegTree {
+                val: if l_bound == r_bound
+                {
+                    M::identity()
+                }
+                else
+                {
+                    leaf(l_bound)
+                },
+                l_node: None,
+                r_node: None,
+                range: (l_bound, r_bound),
+                mid: l_bound,
+            };
+        }
+        let m = l_bound + (r_bound - l_bound) / 2;
+        let left = Self::build(l_bound, m, leaf);
+        let right = Self::build(m, r_bound, leaf);
+        SegTree {
+            val: M::combine(&left.val, &right.val),
+            l_node: Some(Rc::new(RefCell::new(left))),
+            r_node: Some(Rc::new(RefCell::new(right))),
+            range: (l_bound, r_bound),
+            mid: m,
+        }
+    }
+
+    // `build` over `data`, forking into `rayon::join` above
+    // PARALLEL_BUILD_THRESHOLD leaves
+    #[cfg(feature = "rayon")]
+    fn par_build(l_bound: usize, r_bound: usize, data: &[M::Item]) -> SegTree<M>
+    where
+        M::Item: Send + Sync,
+    {
+        if r_bound - l_bound <= PARALLEL_BUILD_THRESHOLD
+        {
+            return Self::build(l_bound, r_bound, &|i| data[i].clone());
+        }
+        let m = l_bound + (r_bound - l_bound) / 2;
+        let (SendTree(left), SendTree(right)) = rayon::join(
+            || SendTree(Self::par_build(l_bound, m, data)),
+            || SendTree(Self::par_build(m, r_bound, data)),
+        );
+        SegTree {
+            val: M::combine(&left.val, &right.val),
+            l_node: Some(Rc::new(RefCell::new(left))),
+            r_node: Some(Rc::new(RefCell::new(right))),
+            range: (l_bound, r_bound),
+            mid: m,
+        }
+    }
+    /// Updates the value at a specific index in the segment tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_pos` - The index to update.
+    /// * `value` - The new value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.revise(2, 10);
+    /// ```
+    pub fn revise(&mut self, target_pos: usize, value: M::Item)
+    {
+        self.try_revise(target_pos, value)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Updates the value at a specific index, returning an error instead
+    /// of panicking if the index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::{SegError, SegTree};
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// assert_eq!(
+    ///     seg_tree.try_revise(10, 1),
+    ///     Err(SegError::OutOfRange { pos: 10 })
+    /// );
+    /// ```
+    pub fn try_revise(&mut self, target_pos: usize, value: M::Item) -> Result<(), SegError>
+    {
+        self.checked_update(target_pos, |_| value).map(|_| ())
+    }
+    /// Updates the value at a specific index, returning the value it held
+    /// before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.revise(4, 10);
+    /// assert_eq!(seg_tree.replace(4, 20), 10);
+    /// ```
+    pub fn replace(&mut self, target_pos: usize, value: M::Item) -> M::Item
+    {
+        self.checked_update(target_pos, |_| value)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Replaces the value at a specific index by `f` of its current value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.revise(3, 4);
+    /// seg_tree.apply(3, |x| x * 2 + 1);
+    /// assert_eq!(seg_tree.get(3), 9);
+    /// ```
+    pub fn apply(&mut self, target_pos: usize, f: impl FnOnce(M::Item) -> M::Item)
+    {
+        self.checked_update(target_pos, |value| f(value.clone()))
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    /// Applies a batch of point updates, recombining every affected node
+    /// once instead of once per update.
+    ///
+    /// The result is the same as calling `revise` for each update in order,
+    /// so a later update to the same index wins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any target index is out of range, before anything is
+    /// written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.revise_many(&[(2, 5), (7, 1), (2, 3)]);
+    /// assert_eq!(seg_tree.total(), 4);
+    /// ```
+    pub fn revise_many(&mut self, updates: &[(usize, M::Item)])
+    {
+        if let Some(&(pos, _)) = updates.iter().find(|(pos, _)| !self.contains(*pos))
+        {
+            panic!("{}", SegError::OutOfRange { pos });
+        }
+        // the sort is stable, so the last update of each index ends up last
+        // in its run
+        let mut sorted: Vec<&(usize, M::Item)> = updates.iter().collect();
+        sorted.sort_by_key(|(pos, _)| *pos);
+        let mut last: Vec<&(usize, M::Item)> = Vec::with_capacity(sorted.len());
+        for update in sorted
+        {
+            match last.last_mut()
+            {
+                Some(prev) if prev.0 == update.0 => *prev = update,
+                _ => last.push(update),
+            }
+        }
+        self.assign_many(&last);
+    }
+
+    // writes the leaves of `updates`, sorted by distinct index, then
+    // recombines each touched node on the way back up
+    fn assign_many(&mut self, updates: &[&(usize, M::Item)])
+    {
+        if updates.is_empty()
+        {
+            return;
+        }
+        if self.is_leaf()
+        {
+            self.val = updates[0].1.clone();
+            return;
+        }
+        let split = updates.partition_point(|(pos, _)| *pos < self.mid);
+        if let Some(left) = &self.l_node
+        {
+            left.borrow_mut().assign_many(&updates[..split]);
+        }
+        if let Some(right) = &self.r_node
+        {
+            right.borrow_mut().assign_many(&updates[split..]);
+        }
+        self.pull();
+    }
+
+    /// Exchanges the values at indices `i` and `j`, writing both leaves in
+    /// a single pass down the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = (0..10).collect();
+    /// seg_tree.swap(0, 9);
+    /// assert_eq!(seg_tree.get(0), 9);
+    /// assert_eq!(seg_tree.get(9), 0);
+    /// ```
+    pub fn swap(&mut self, i: usize, j: usize)
+    {
+        for pos in [i, j]
+        {
+            if !self.contains(pos)
+            {
+                panic!("{}", SegError::OutOfRange { pos });
+            }
+        }
+        if i == j
+        {
+            return;
+        }
+        let (lo, hi) = (i.min(j), i.max(j));
+        let (lo_val, hi_val) = (self.leaf(lo), self.leaf(hi));
+        self.assign_pair((lo, hi_val), (hi, lo_val));
+    }
+
+    // writes two distinct leaves, `lo.0 < hi.0`, sharing the path down to
+    // the node where they part
+    fn assign_pair(&mut self, lo: (usize, M::Item), hi: (usize, M::Item))
+    {
+        if hi.0 < self.mid
+        {
+            if let Some(left) = &self.l_node
+            {
+                left.borrow_mut().assign_pair(lo, hi);
+            }
+        }
+        else if lo.0 >= self.mid
+        {
+            if let Some(right) = &self.r_node
+            {
+                right.borrow_mut().assign_pair(lo, hi);
+            }
+        }
+        else
+        {
+            if let Some(left) = &self.l_node
+            {
+                left.borrow_mut()
+                    .update(lo.0, |_| lo.1)
+                    .unwrap_or_else(|err| panic!("{}", err));
+            }
+            if let Some(right) = &self.r_node
+            {
+                right
+                    .borrow_mut()
+                    .update(hi.0, |_| hi.1)
+                    .unwrap_or_else(|err| panic!("{}", err));
+            }
+        }
+        self.pull();
+    }
+
+    fn checked_update(
+        &mut self,
+        target_pos: usize,
+        f: impl FnOnce(&M::Item) -> M::Item,
+    ) -> Result<M::Item, SegError>
+    {
+        if !self.contains(target_pos)
+        {
+            return Err(SegError::OutOfRange { pos: target_pos });
+        }
+        self.update(target_pos, f)
+    }
+
+    // replaces the leaf at `target_pos` by `f` of its current value,
+    // returning the value it held before
+    //
+    // Every borrow on the path is taken before the leaf is written, so a
+    // `Concurrent` error leaves the tree untouched.
+    fn update(
+        &mut self,
+        target_pos: usize,
+        f: impl FnOnce(&M::Item) -> M::Item,
+    ) -> Result<M::Item, SegError>
+    {
+        if (target_pos, target_pos + 1) == self.range
+        {
+            let value = f(&self.val);
+            return Ok(core::mem::replace(&mut self.val, value));
+        }
+        let go_left = target_pos < self.mid;
+        let (path, sibling) = if go_left
+        {
+            (&self.l_node, &self.r_node)
+        }
+        else
+        {
+            (&self.r_node, &self.l_node)
+        };
+        let sibling_val = Self::child_val(sibling)?;
+        let Some(path) = path
+        else
+        {
+            return Ok(M::identity());
+        };
+        let mut child = path.try_borrow_mut().map_err(|_| SegError::Concurrent)?;
+        let old = child.update(target_pos, f)?;
+        self.val = if go_left
+        {
+            M::combine(&child.val, &sibling_val)
+        }
+        else
+        {
+            M::combine(&sibling_val, &child.val)
+        };
+        Ok(old)
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// An empty range `l == r` yields the identity.
+    ///
+    /// # Arguments
+    ///
+    /// * `l` - The left bound of the query range.
+    /// * `r` - The right bound of the query range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 10);
+    /// let sum = seg_tree.ask(0, 5);
+    /// ```
+    ///
+    /// The result is `#[must_use]`, so a discarded query is flagged:
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.ask(0, 1);
+    /// ```
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        self.try_ask(l, r).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Queries `[l, r)` like `ask`, but returns the identity for any
+    /// degenerate range with `l >= r` instead of panicking on a reversed one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l` or `r` lies outside `[range.0, range.1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// assert_eq!(seg_tree.ask_or_identity(5, 5), 0);
+    /// assert_eq!(seg_tree.ask_or_identity(7, 3), 0);
+    /// assert_eq!(seg_tree.ask_or_identity(3, 7), 18);
+    /// ```
+    #[must_use]
+    pub fn ask_or_identity(&self, l: usize, r: usize) -> M::Item
+    {
+        let in_bounds = |bound| self.range.0 <= bound && bound <= self.range.1;
+        if !in_bounds(l) || !in_bounds(r)
+        {
+            panic!("{}", SegError::InvalidRange { l, r });
+        }
+        if l >= r
+        {
+            return M::identity();
+        }
+        self.ask(l, r)
+    }
+    /// Answers every query `(l, r)` in `queries` like `ask(l, r)`, returning
+    /// the results in order.
+    ///
+    /// All ranges are checked up front, before any query runs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any query range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// assert_eq!(seg_tree.ask_many(&[(0, 10), (2, 4), (5, 5)]), vec![45, 5, 0]);
+    /// ```
+    #[must_use]
+    pub fn ask_many(&self, queries: &[(usize, usize)]) -> Vec<M::Item>
+    {
+        if let Some(&(l, r)) = queries.iter().find(|&&(l, r)| !self.contains_range(l, r))
+        {
+            panic!("{}", SegError::InvalidRange { l, r });
+        }
+        queries
+            .iter()
+            .map(|&(l, r)| {
+                if l == r
+                {
+                    return M::identity();
+                }
+                self.query(l, r).unwrap_or_else(|err| panic!("{}", err))
+            })
+            .collect()
+    }
+    /// Queries the combined value of the closed range `[l, r]`, for callers
+    /// used to inclusive bounds. This is `ask(l, r + 1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r` or `[l, r]` is not contained in the tree's range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// assert_eq!(seg_tree.ask_inclusive(3, 6), seg_tree.ask(3, 7));
+    /// ```
+    #[must_use]
+    pub fn ask_inclusive(&self, l: usize, r: usize) -> M::Item
+    {
+        if l > r || r >= self.range.1
+        {
+            panic!("Invalid query range: [{}, {}]", l, r);
+        }
+        self.ask(l, r + 1)
+    }
+    /// Queries the combined value of `[l, r)`, returning an error instead
+    /// of panicking if the query range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::{SegError, SegTree};
+    /// let seg_tree: SegTree = SegTree::new(0, 10);
+    /// assert_eq!(
+    ///     seg_tree.try_ask(10, 0),
+    ///     Err(SegError::InvalidRange { l: 10, r: 0 })
+    /// );
+    /// ```
+    pub fn try_ask(&self, l: usize, r: usize) -> Result<M::Item, SegError>
+    {
+        if !self.contains_range(l, r)
+        {
+            return Err(SegError::InvalidRange { l, r });
+        }
+        if l == r
+        {
+            return Ok(M::identity());
+        }
+        self.query(l, r)
+    }
+
+    fn query(&self, l: usize, r: usize) -> Result<M::Item, SegError>
+    {
+        if (l, r) == self.range
+        {
+            Ok(self.val.clone())
+        }
+        else if r <= self.mid
+        {
+            Self::query_child(&self.l_node, l, r)
+        }
+        else if l >= self.mid
+        {
+            Self::query_child(&self.r_node, l, r)
+        }
+        else
+        {
+            let left_val = Self::query_child(&self.l_node, l, self.mid)?;
+            let right_val = Self::query_child(&self.r_node, self.mid, r)?;
+            // left before right, which non-commutative monoids rely on
+            Ok(M::combine(&left_val, &right_val))
+        }
+    }
+
+    fn query_child(
+        child: &Option<Rc<RefCell<SegTree<M>>>>,
+        l: usize,
+        r: usize,
+    ) -> Result<M::Item, SegError>
+    {
+        match child
+        {
+            Some(node) => node
+                .try_borrow()
+                .map_err(|_| SegError::Concurrent)?
+                .query(l, r),
+            None => Ok(M::identity()),
+        }
+    }
+
+    fn child_val(child: &Option<Rc<RefCell<SegTree<M>>>>) -> Result<M::Item, SegError>
+    {
+        match child
+        {
+            Some(node) => Ok(node
+                .try_borrow()
+                .map_err(|_| SegError::Concurrent)?
+                .val
+                .clone()),
+            None => Ok(M::identity()),
+        }
+    }
+
+    /// Returns the value at a single index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.revise(7, 3);
+    /// assert_eq!(seg_tree.get(7), 3);
+    /// ```
+    #[must_use]
+    pub fn get(&self, pos: usize) -> M::Item
+    {
+        if !self.contains(pos)
+        {
+            panic!("{}", SegError::OutOfRange { pos });
+        }
+        self.leaf(pos)
+    }
+
+    fn leaf(&self, pos: usize) -> M::Item
+    {
+        let child = if pos < self.mid
+        {
+            &self.l_node
+        }
+        else
+        {
+            &self.r_node
+        };
+        match child
+        {
+            Some(node) => node.borrow().leaf(pos),
+            None => self.val.clone(),
+        }
+    }
+
+    /// Returns the largest `r` such that `pred(ask(l, r))` holds, descending
+    /// the tree in O(log n) instead of binary-searching with `ask`.
+    ///
+    /// `pred` must be monotone: once it fails for some `r` it must fail for
+    /// every larger `r`. `pred(identity)` must hold, and `l` itself is
+    /// returned when even the first element fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l` lies outside `[range.0, range.1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// assert_eq!(seg_tree.max_right(0, |&sum| sum <= 10), 5);
+    /// ```
+    pub fn max_right(&self, l: usize, pred: impl Fn(&M::Item) -> bool) -> usize
+    {
+        if l < self.range.0 || l > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        let mut acc = M::identity();
+        self.max_right_node(l, &pred, &mut acc)
+            .unwrap_or(self.range.1)
+    }
+
+    // folds the part of this node right of `l` into `acc` while `pred`
+    // holds, returning the position where it first fails
+    fn max_right_node(
+        &self,
+        l: usize,
+        pred: &impl Fn(&M::Item) -> bool,
+        acc: &mut M::Item,
+    ) -> Option<usize>
+    {
+        if self.range.1 <= l
+        {
+            return None;
+        }
+        if l <= self.range.0
+        {
+            let next = M::combine(acc, &self.val);
+            if pred(&next)
+            {
+                *acc = next;
+                return None;
+            }
+            if self.is_leaf()
+            {
+                return Some(self.range.0);
+            }
+        }
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            if let Some(r) = child.borrow().max_right_node(l, pred, acc)
+            {
+                return Some(r);
+            }
+        }
+        None
+    }
+
+    /// Returns the smallest `l` such that `pred(ask(l, r))` holds, the
+    /// mirror image of [`max_right`](Self::max_right).
+    ///
+    /// `pred` must be monotone: once it fails for some `l` it must fail for
+    /// every smaller `l`. `pred(identity)` must hold, and `r` itself is
+    /// returned when even the last element fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `r` lies outside `[range.0, range.1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    /// assert_eq!(seg_tree.min_left(10, |&sum| sum <= 20), 8);
+    /// ```
+    pub fn min_left(&self, r: usize, pred: impl Fn(&M::Item) -> bool) -> usize
+    {
+        if r < self.range.0 || r > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        let mut acc = M::identity();
+        self.min_left_node(r, &pred, &mut acc)
+            .unwrap_or(self.range.0)
+    }
+
+    // folds the part of this node left of `r` into `acc` while `pred`
+    // holds, returning the position just right of where it first fails
+    fn min_left_node(
+        &self,
+        r: usize,
+        pred: &impl Fn(&M::Item) -> bool,
+        acc: &mut M::Item,
+    ) -> Option<usize>
+    {
+        if self.range.0 >= r
+        {
+            return None;
+        }
+        if self.range.1 <= r
+        {
+            let next = M::combine(&self.val, acc);
+            if pred(&next)
+            {
+                *acc = next;
+                return None;
+            }
+            if self.is_leaf()
+            {
+                return Some(self.range.1);
+            }
+        }
+        for child in self.r_node.iter().chain(self.l_node.iter())
+        {
+            if let Some(l) = child.borrow().min_left_node(r, pred, acc)
+            {
+                return Some(l);
+            }
+        }
+        None
+    }
+
+    /// Returns the smallest index `>= l` whose leaf value satisfies `pred`,
+    /// or `None` if there is none.
+    ///
+    /// Unlike [`max_right`](Self::max_right), `pred` looks at single values
+    /// and need not be monotone, so this visits every leaf from `l` on in
+    /// the worst case. See [`find_first_pruned`](Self::find_first_pruned)
+    /// for predicates the node aggregates can rule out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l` lies outside `[range.0, range.1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 3, 7, 2, 9]);
+    /// assert_eq!(seg_tree.find_first(0, |&x| x > 5), Some(2));
+    /// assert_eq!(seg_tree.find_first(3, |&x| x > 5), Some(4));
+    /// assert_eq!(seg_tree.find_first(0, |&x| x > 9), None);
+    /// ```
+    pub fn find_first(&self, l: usize, pred: impl Fn(&M::Item) -> bool) -> Option<usize>
+    {
+        if l < self.range.0 || l > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        self.find_first_node(l, &pred, &|_| true)
+    }
+    /// Like [`find_first`](Self::find_first), but skips every subtree
+    /// whose combined value fails `pred`.
+    ///
+    /// This is only correct when `pred` holds for the combined value of any
+    /// range containing a leaf it holds for, as with `x > 5` on a max tree.
+    /// Then only the paths towards the answer are descended, in O(log n)
+    /// when `l` is the start of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l` lies outside `[range.0, range.1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree = SegTree::new_max(0, 5);
+    /// for (i, x) in [1, 3, 7, 2, 9].into_iter().enumerate()
+    /// {
+    ///     seg_tree.revise(i, x);
+    /// }
+    /// assert_eq!(seg_tree.find_first_pruned(0, |&x| x > 5), Some(2));
+    /// ```
+    pub fn find_first_pruned(&self, l: usize, pred: impl Fn(&M::Item) -> bool) -> Option<usize>
+    {
+        if l < self.range.0 || l > self.range.1
+        {
+            panic!("Invalid query range");
+        }
+        self.find_first_node(l, &pred, &pred)
+    }
+
+    // leftmost leaf at or right of `l` satisfying `pred`, skipping nodes
+    // whose value fails `prune`
+    fn find_first_node(
+        &self,
+        l: usize,
+        pred: &impl Fn(&M::Item) -> bool,
+        prune: &impl Fn(&M::Item) -> bool,
+    ) -> Option<usize>
+    {
+        if self.range.1 <= l || !prune(&self.val)
+        {
+            return None;
+        }
+        if self.is_leaf()
+        {
+            return pred(&self.val).then_some(self.range.0);
+        }
+        self.l_node
+            .iter()
+            .chain(self.r_node.iter())
+            .find_map(|child| child.borrow().find_first_node(l, pred, prune))
+    }
+
+    /// Resets every value to the identity, reusing the existing nodes
+    /// instead of reallocating them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// seg_tree.clear();
+    /// assert_eq!(seg_tree.ask(0, 3), 0);
+    /// ```
+    pub fn clear(&mut self)
+    {
+        self.val = M::identity();
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow_mut().clear();
+        }
+    }
+
+    /// Returns `ask(l, r)` and resets every value in `[l, r)` to the
+    /// identity, in a single descent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = (0..9).collect();
+    /// assert_eq!(seg_tree.drain_range(2, 5), 9);
+    /// assert_eq!(seg_tree.to_vec(), vec![0, 1, 0, 0, 0, 5, 6, 7, 8]);
+    /// ```
+    pub fn drain_range(&mut self, l: usize, r: usize) -> M::Item
+    {
+        if !self.contains_range(l, r)
+        {
+            panic!("{}", SegError::InvalidRange { l, r });
+        }
+        self.drain_node(l, r)
+    }
+
+    fn drain_node(&mut self, l: usize, r: usize) -> M::Item
+    {
+        if r <= self.range.0 || self.range.1 <= l
+        {
+            return M::identity();
+        }
+        if l <= self.range.0 && self.range.1 <= r
+        {
+            let drained = self.val.clone();
+            self.clear();
+            return drained;
+        }
+        let mut drained = M::identity();
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            drained = M::combine(&drained, &child.borrow_mut().drain_node(l, r));
+        }
+        self.pull();
+        drained
+    }
+
+    /// Sets every leaf to `value` and recomputes the internal nodes, without
+    /// any lazy machinery.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.fill(3);
+    /// assert_eq!(seg_tree.total(), 30);
+    /// ```
+    pub fn fill(&mut self, value: M::Item)
+    {
+        if self.is_leaf()
+        {
+            self.val = value;
+            return;
+        }
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow_mut().fill(value.clone());
+        }
+        self.pull();
+    }
+
+    /// Overwrites every leaf with `data`, `data[i]` going to index
+    /// `range.0 + i`, and recomputes the internal nodes bottom-up.
+    ///
+    /// This is the in-place counterpart to `from_slice`: the existing nodes
+    /// are reused instead of reallocated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len() != self.len()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// seg_tree.rebuild(&[4, 5, 6]);
+    /// assert_eq!(seg_tree.ask(0, 2), 9);
+    /// ```
+    pub fn rebuild(&mut self, data: &[M::Item])
+    {
+        if data.len() != self.len()
+        {
+            panic!("Data length does not match the tree length");
+        }
+        self.rebuild_node(data, self.range.0);
+    }
+
+    fn rebuild_node(&mut self, data: &[M::Item], offset: usize)
+    {
+        if self.is_leaf()
+        {
+            self.val = data[self.range.0 - offset].clone();
+            return;
+        }
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow_mut().rebuild_node(data, offset);
+        }
+        self.pull();
+    }
+
+    /// Sets every leaf of `self` to `f(self_leaf, other_leaf)`, pairing the
+    /// leaves at the same index, and recomputes the internal nodes.
+    ///
+    /// The two trees may have different shapes; the merged values are
+    /// written back through `rebuild`, so the nodes of `self` are reused.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the trees cover different ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::from_slice(&[1, 5, 2]);
+    /// let other: SegTree = SegTree::from_slice(&[4, 3, 6]);
+    /// seg_tree.merge_with(&other, i32::max);
+    /// assert_eq!(seg_tree.to_vec(), vec![4, 5, 6]);
+    /// ```
+    pub fn merge_with(&mut self, other: &SegTree<M>, f: impl Fn(M::Item, M::Item) -> M::Item)
+    {
+        if self.range != other.range
+        {
+            panic!("Trees cover different ranges");
+        }
+        let merged: Vec<M::Item> = self
+            .iter()
+            .zip(other.iter())
+            .map(|(a, b)| f(a, b))
+            .collect();
+        self.rebuild(&merged);
+    }
+
+    /// Returns a new tree with the same range and shape whose leaf `i` is
+    /// `f` applied to leaf `i` of `self`, leaving `self` untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// let doubled = seg_tree.map(|x| x * 2);
+    /// assert_eq!(doubled.total(), 12);
+    /// assert_eq!(seg_tree.total(), 6);
+    /// ```
+    pub fn map(&self, f: impl Fn(M::Item) -> M::Item) -> SegTree<M>
+    {
+        self.map_node(&f)
+    }
+
+    fn map_node(&self, f: &impl Fn(M::Item) -> M::Item) -> SegTree<M>
+    {
+        let map_child = |child: &Option<Rc<RefCell<SegTree<M>>>>| {
+            child
+                .as_ref()
+                .map(|node| Rc::new(RefCell::new(node.borrow().map_node(f))))
+        };
+        let mut node = SegTree {
+            val: M::identity(),
+            range: self.range,
+            mid: self.mid,
+            l_node: map_child(&self.l_node),
+            r_node: map_child(&self.r_node),
+        };
+        if self.is_leaf()
+        {
+            node.val = f(self.val.clone());
+        }
+        else
+        {
+            node.pull();
+        }
+        node
+    }
+
+    /// Appends `value` at index `range.1`, growing the range by one like
+    /// `Vec::push`.
+    ///
+    /// The new leaf is hung off the right spine, under the highest node whose
+    /// right half is not yet smaller than its left, so repeated pushes keep
+    /// the height logarithmic and no node is rebuilt. Runs in O(log n).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// seg_tree.push(4);
+    /// assert_eq!(seg_tree.len(), 4);
+    /// assert_eq!(seg_tree.ask(3, 4), 4);
+    /// ```
+    pub fn push(&mut self, value: M::Item)
+    {
+        let len_of = |child: &Option<Rc<RefCell<SegTree<M>>>>| {
+            child.as_ref().map_or(0, |node| node.borrow().len())
+        };
+        if !self.is_leaf() && len_of(&self.r_node) < len_of(&self.l_node)
+        {
+            if let Some(right) = &self.r_node
+            {
+                right.borrow_mut().push(value);
+            }
+            self.range.1 += 1;
+            self.pull();
+            return;
+        }
+        let pos = self.range.1;
+        let placeholder = SegTree {
+            val: M::identity(),
+            range: self.range,
+            mid: self.mid,
+            l_node: None,
+            r_node: None,
+        };
+        let old = core::mem::replace(self, placeholder);
+        *self = SegTree::concat(old, SegTree::new_with(pos, pos + 1, value));
+    }
+
+    /// Moves the tree to cover `[range.0 + offset, range.1 + offset)`,
+    /// keeping every value at the same position relative to the start.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a shifted bound would fall below `0` or overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = (0..10).collect();
+    /// seg_tree.shift(5);
+    /// assert_eq!(seg_tree.ask(5, 15), 45);
+    /// ```
+    pub fn shift(&mut self, offset: isize)
+    {
+        // every other bound lies between these two, so checking them is
+        // enough
+        if self.range.0.checked_add_signed(offset).is_none()
+            || self.range.1.checked_add_signed(offset).is_none()
+        {
+            panic!("Shifted range out of bounds");
+        }
+        self.shift_node(offset);
+    }
+
+    fn shift_node(&mut self, offset: isize)
+    {
+        self.range = (
+            self.range.0.wrapping_add_signed(offset),
+            self.range.1.wrapping_add_signed(offset),
+        );
+        self.mid = self.mid.wrapping_add_signed(offset);
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow_mut().shift_node(offset);
+        }
+    }
+
+    /// Splits the tree into two independent trees covering `[range.0, mid)`
+    /// and `[mid, range.1)`.
+    ///
+    /// Every subtree lying entirely on one side and already covering the
+    /// range the new tree needs there is moved over as is; only the nodes
+    /// straddling `mid` are rebuilt.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `range.0 < mid < range.1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// let (left, right) = seg_tree.split(4);
+    /// assert_eq!(left.total(), 6);
+    /// assert_eq!(right.get_range(), (4, 10));
+    /// ```
+    pub fn split(mut self, mid: usize) -> (SegTree<M>, SegTree<M>)
+    {
+        let (l, r) = self.range;
+        if mid <= l || mid >= r
+        {
+            panic!("Invalid split position");
+        }
+        let left = self.take_range(l, mid);
+        let right = self.take_range(mid, r);
+        (left, right)
+    }
+
+    /// Joins two trees over adjacent ranges into one tree covering both,
+    /// moving them under a fresh root.
+    ///
+    /// The new root splits at `left.range.1` rather than at the midpoint, so
+    /// long chains of uneven concatenations can make the tree deeper than a
+    /// freshly built one. If either tree is empty, the other is returned as
+    /// is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `left.range.1 != right.range.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let left: SegTree = SegTree::from_slice(&[1, 2]);
+    /// let right: SegTree = SegTree::new_with(2, 5, 1);
+    /// let seg_tree = SegTree::concat(left, right);
+    /// assert_eq!(seg_tree.get_range(), (0, 5));
+    /// assert_eq!(seg_tree.to_vec(), vec![1, 2, 1, 1, 1]);
+    /// ```
+    pub fn concat(left: SegTree<M>, right: SegTree<M>) -> SegTree<M>
+    {
+        if left.range.1 != right.range.0
+        {
+            panic!("Ranges to concatenate are not adjacent");
+        }
+        // an empty side would only add a childless node to the tree
+        if left.is_empty()
+        {
+            return right;
+        }
+        if right.is_empty()
+        {
+            return left;
+        }
+        SegTree {
+            val: M::combine(&left.val, &right.val),
+            range: (left.range.0, right.range.1),
+            mid: left.range.1,
+            l_node: Some(Rc::new(RefCell::new(left))),
+            r_node: Some(Rc::new(RefCell::new(right))),
+        }
+    }
+
+    // moves out a tree over [l, r), a subrange of this node: nodes covering
+    // exactly a wanted range are taken over, leaving a childless placeholder
+    // behind, and new nodes split at the midpoint like `build`
+    fn take_range(&mut self, l: usize, r: usize) -> SegTree<M>
+    {
+        if (l, r) == self.range
+        {
+            let placeholder = SegTree {
+                val: M::identity(),
+                range: self.range,
+                mid: self.mid,
+                l_node: None,
+                r_node: None,
+            };
+            return core::mem::replace(self, placeholder);
+        }
+        let child = if r <= self.mid
+        {
+            &self.l_node
+        }
+        else if l >= self.mid
+        {
+            &self.r_node
+        }
+        else
+        {
+            // [l, r) straddles this node's midpoint, so its halves have to be
+            // gathered separately
+            let mid = (l + r) / 2;
+            let left = self.take_range(l, mid);
+            let right = self.take_range(mid, r);
+            return SegTree {
+                val: M::combine(&left.val, &right.val),
+                range: (l, r),
+                mid,
+                l_node: Some(Rc::new(RefCell::new(left))),
+                r_node: Some(Rc::new(RefCell::new(right))),
+            };
+        };
+        match child
+        {
+            Some(node) => node.borrow_mut().take_range(l, r),
+            None => SegTree::new(l, r),
+        }
+    }
+
+    /// Returns all leaf values in index order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[3, 1, 2]);
+    /// assert_eq!(seg_tree.to_vec(), vec![3, 1, 2]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<M::Item>
+    {
+        let mut leaves = Vec::with_capacity(self.range.1 - self.range.0);
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_leaves(&self, leaves: &mut Vec<M::Item>)
+    {
+        if self.is_leaf()
+        {
+            leaves.push(self.val.clone());
+            return;
+        }
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow().collect_leaves(leaves);
+        }
+    }
+
+    // recomputes this node's value from its children
+    fn pull(&mut self)
+    {
+        self.val = M::combine(
+            &self
+                .l_node
+                .as_ref()
+                .map_or(M::identity(), |left| left.borrow().val.clone()),
+            &self
+                .r_node
+                .as_ref()
+                .map_or(M::identity(), |right| right.borrow().val.clone()),
+        );
+    }
+
+    // an empty tree has no children either, but is not a leaf
+    fn is_leaf(&self) -> bool
+    {
+        self.len() == 1
+    }
+
+    /// Returns an iterator over the leaf values in index order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[3, 1, 2]);
+    /// assert_eq!(seg_tree.iter().max(), Some(3));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, M>
+    {
+        let mut iter = Iter {
+            root: None,
+            stack: Vec::new(),
+        };
+        if self.is_leaf()
+        {
+            iter.root = Some(self);
+        }
+        else
+        {
+            iter.stack.extend(self.r_node.iter().cloned());
+            iter.stack.extend(self.l_node.iter().cloned());
+        }
+        iter
+    }
+
+    /// Returns an iterator over the range and value of every node,
+    /// internal nodes included, breadth-first from the root and left to
+    /// right within a level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    /// let ranges: Vec<_> = seg_tree.level_order().map(|(range, _)| range).take(3).collect();
+    /// assert_eq!(ranges, vec![(0, 4), (0, 2), (2, 4)]);
+    /// ```
+    pub fn level_order(&self) -> LevelOrder<'_, M>
+    {
+        LevelOrder {
+            root: Some(self),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Returns the combined value of the whole range in O(1), read straight
+    /// from the root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// assert_eq!(seg_tree.total(), 6);
+    /// ```
+    #[must_use]
+    pub fn total(&self) -> M::Item
+    {
+        self.val.clone()
+    }
+    /// Returns the number of elements covered by the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 10);
+    /// assert_eq!(seg_tree.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.range.1 - self.range.0
+    }
+    /// Returns `true` if the tree covers no elements.
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+    /// Returns `true` if `pos` is an index of the tree, that is, if
+    /// `revise(pos, ..)` would not panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(3, 9);
+    /// assert!(seg_tree.contains(3));
+    /// assert!(!seg_tree.contains(9));
+    /// ```
+    pub fn contains(&self, pos: usize) -> bool
+    {
+        self.range.0 <= pos && pos < self.range.1
+    }
+    /// Returns `true` if `[l, r)` lies within the tree, that is, if
+    /// `ask(l, r)` would not panic.
+    ///
+    /// Like `ask`, this accepts an empty range `l == r` inside the tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// if seg_tree.contains_range(2, 5)
+    /// {
+    ///     assert_eq!(seg_tree.ask(2, 5), 9);
+    /// }
+    /// assert!(!seg_tree.contains_range(5, 11));
+    /// assert!(!seg_tree.contains_range(5, 2));
+    /// ```
+    pub fn contains_range(&self, l: usize, r: usize) -> bool
+    {
+        self.range.0 <= l && l <= r && r <= self.range.1
+    }
+    /// Visits every node in pre-order, calling `f` with its range, its
+    /// value and its depth, the root being at depth `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    /// let mut ranges = Vec::new();
+    /// seg_tree.walk(&mut |range, _, depth| {
+    ///     if depth == 1
+    ///     {
+    ///         ranges.push(range);
+    ///     }
+    /// });
+    /// assert_eq!(ranges, vec![(0, 2), (2, 4)]);
+    /// ```
+    pub fn walk(&self, f: &mut impl FnMut((usize, usize), &M::Item, usize))
+    {
+        self.walk_node(f, 0);
+    }
+
+    fn walk_node(&self, f: &mut impl FnMut((usize, usize), &M::Item, usize), depth: usize)
+    {
+        f(self.range, &self.val, depth);
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow().walk_node(f, depth + 1);
+        }
+    }
+    /// Returns the number of edges on the longest path from the root down
+    /// to a leaf, `0` for a single-leaf tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 8);
+    /// assert_eq!(seg_tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> usize
+    {
+        self.l_node
+            .iter()
+            .chain(self.r_node.iter())
+            .map(|child| child.borrow().height() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+    /// Returns the number of allocated nodes, internal nodes included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 8);
+    /// assert_eq!(seg_tree.node_count(), 15);
+    /// ```
+    pub fn node_count(&self) -> usize
+    {
+        1 + self
+            .l_node
+            .iter()
+            .chain(self.r_node.iter())
+            .map(|child| child.borrow().node_count())
+            .sum::<usize>()
+    }
+    /// Estimates the memory taken by the tree in bytes: the root itself plus
+    /// one `Rc<RefCell<..>>` allocation, with its reference counts, per other
+    /// node.
+    ///
+    /// This is an approximation: allocator overhead and any heap memory owned
+    /// by the items themselves are not counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 1000);
+    /// assert!(seg_tree.memory_bytes() > 1999 * std::mem::size_of::<SegTree>());
+    /// ```
+    pub fn memory_bytes(&self) -> usize
+    {
+        // an Rc allocation holds the strong and weak counts before the value
+        let per_node =
+            2 * core::mem::size_of::<usize>() + core::mem::size_of::<RefCell<SegTree<M>>>();
+        core::mem::size_of::<SegTree<M>>() + (self.node_count() - 1) * per_node
+    }
+
+    /// Returns the range covered by the root's left child, or `None` at a
+    /// leaf.
+    ///
+    /// Together with [`right_range`](Self::right_range), this shows where
+    /// the root splits its range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::new(0, 10);
+    /// assert_eq!(seg_tree.left_range(), Some((0, 5)));
+    /// assert_eq!(seg_tree.right_range(), Some((5, 10)));
+    /// ```
+    pub fn left_range(&self) -> Option<(usize, usize)>
+    {
+        self.l_node.as_ref().map(|left| left.borrow().range)
+    }
+    /// Returns the range covered by the root's right child, or `None` at a
+    /// leaf.
+    pub fn right_range(&self) -> Option<(usize, usize)>
+    {
+        self.r_node.as_ref().map(|right| right.borrow().range)
+    }
+    /// Returns the combined value of the root's left child, or `None` at a
+    /// leaf.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    /// assert_eq!(seg_tree.left_val(), Some(3));
+    /// assert_eq!(seg_tree.right_val(), Some(7));
+    /// ```
+    pub fn left_val(&self) -> Option<M::Item>
+    {
+        self.l_node.as_ref().map(|left| left.borrow().val.clone())
+    }
+    /// Returns the combined value of the root's right child, or `None` at a
+    /// leaf.
+    pub fn right_val(&self) -> Option<M::Item>
+    {
+        self.r_node.as_ref().map(|right| right.borrow().val.clone())
+    }
+
+    // for testing: overwrites a node's value without touching its
+    // ancestors, breaking the aggregation invariant on purpose
+    #[cfg(test)]
+    pub(crate) fn set_val_unchecked(&mut self, value: M::Item)
+    {
+        self.val = value;
+    }
+
+    // for testing: the largest strong count of any node `Rc`, which must be
+    // `1` for `Index` to be sound
+    #[cfg(test)]
+    pub(crate) fn max_node_refs(&self) -> usize
+    {
+        self.l_node
+            .iter()
+            .chain(self.r_node.iter())
+            .map(|node| Rc::strong_count(node).max(node.borrow().max_node_refs()))
+            .max()
+            .unwrap_or(1)
+    }
+
+    // for testing: hands out a node so that tests can hold a borrow on it
+    #[cfg(test)]
+    pub(crate) fn left_child(&self) -> Option<Rc<RefCell<SegTree<M>>>>
+    {
+        self.l_node.clone()
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> M::Item
+    {
+        self.val.clone()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
+
+/// Deep-copies every node, so that the clone can be revised without
+/// affecting the original through shared `RefCell`s.
+impl<M: Monoid> Clone for SegTree<M>
+{
+    fn clone(&self) -> SegTree<M>
+    {
+        SegTree {
+            val: self.val.clone(),
+            range: self.range,
+            mid: self.mid,
+            l_node: self
+                .l_node
+                .as_ref()
+                .map(|left| Rc::new(RefCell::new(left.borrow().clone()))),
+            r_node: self
+                .r_node
+                .as_ref()
+                .map(|right| Rc::new(RefCell::new(right.borrow().clone()))),
+        }
+    }
+}
+
+/// Two trees are equal when they cover the same range and hold the same
+/// leaf values, which is checked leaf by leaf.
+impl<M: Monoid> PartialEq for SegTree<M>
+where
+    M::Item: PartialEq,
+{
+    fn eq(&self, other: &SegTree<M>) -> bool
+    {
+        self.range == other.range && self.iter().eq(other.iter())
+    }
+}
+
+impl<M: Monoid> Eq for SegTree<M> where M::Item: Eq {}
+
+/// Hashes the range and the leaf values in index order, matching
+/// `PartialEq`, so that equal trees of different shapes hash equal.
+///
+/// No method taking `&self` changes a value through the inner `RefCell`s,
+/// so a tree stays a valid key in a `HashSet` or `HashMap`.
+impl<M: Monoid> Hash for SegTree<M>
+where
+    M::Item: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.range.hash(state);
+        for value in self.iter()
+        {
+            value.hash(state);
+        }
+    }
+}
+
+impl<M: Monoid> SegTree<M>
+where
+    M::Item: PartialEq,
+{
+    /// Updates the value at a specific index like `revise`, but returns
+    /// `false` without recombining any ancestor when the leaf already holds
+    /// `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// assert!(seg_tree.revise_checked(3, 5));
+    /// assert!(!seg_tree.revise_checked(3, 5));
+    /// ```
+    pub fn revise_checked(&mut self, target_pos: usize, value: M::Item) -> bool
+    {
+        if !self.contains(target_pos)
+        {
+            panic!("{}", SegError::OutOfRange { pos: target_pos });
+        }
+        if self.leaf(target_pos) == value
+        {
+            return false;
+        }
+        self.revise(target_pos, value);
+        true
+    }
+    /// Returns the indices at which `self` and `other` hold different values,
+    /// in increasing order.
+    ///
+    /// Equal aggregates do not imply equal leaves (`[1, 2]` and `[2, 1]` have
+    /// the same sum), so no subtree can be skipped and every leaf is compared
+    /// in O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two trees cover different ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// let mut changed = seg_tree.clone();
+    /// changed.revise(3, 0);
+    /// assert_eq!(seg_tree.diff(&changed), vec![3]);
+    /// ```
+    pub fn diff(&self, other: &SegTree<M>) -> Vec<usize>
+    {
+        if self.range != other.range
+        {
+            panic!("Trees cover different ranges");
+        }
+        self.iter()
+            .zip(other.iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| self.range.0 + i)
+            .collect()
+    }
+}
+
+/// An iterator over the leaf values of a [`SegTree`], created by
+/// [`SegTree::iter`].
+pub struct Iter<'a, M: Monoid>
+{
+    // set only when the root itself is a leaf
+    root: Option<&'a SegTree<M>>,
+    // nodes still to visit, the next one on top
+    stack: Vec<Rc<RefCell<SegTree<M>>>>,
+}
+
+impl<M: Monoid> Iterator for Iter<'_, M>
+{
+    type Item = M::Item;
+
+    fn next(&mut self) -> Option<M::Item>
+    {
+        if let Some(root) = self.root.take()
+        {
+            return Some(root.val.clone());
+        }
+        while let Some(node) = self.stack.pop()
+        {
+            let node = node.borrow();
+            if node.is_leaf()
+            {
+                return Some(node.val.clone());
+            }
+            self.stack.extend(node.r_node.iter().cloned());
+            self.stack.extend(node.l_node.iter().cloned());
+        }
+        None
+    }
+}
+
+/// An iterator over the `(range, value)` pairs of all nodes of a
+/// [`SegTree`] in level order, created by [`SegTree::level_order`].
+pub struct LevelOrder<'a, M: Monoid>
+{
+    // taken on the first call to `next`
+    root: Option<&'a SegTree<M>>,
+    // nodes still to visit, the next one at the front
+    queue: VecDeque<Rc<RefCell<SegTree<M>>>>,
+}
+
+impl<M: Monoid> Iterator for LevelOrder<'_, M>
+{
+    type Item = ((usize, usize), M::Item);
+
+    fn next(&mut self) -> Option<((usize, usize), M::Item)>
+    {
+        let visit = |node: &SegTree<M>, queue: &mut VecDeque<_>| {
+            queue.extend(node.l_node.iter().cloned());
+            queue.extend(node.r_node.iter().cloned());
+            (node.range, node.val.clone())
+        };
+        if let Some(root) = self.root.take()
+        {
+            return Some(visit(root, &mut self.queue));
+        }
+        let node = self.queue.pop_front()?;
+        let node = node.borrow();
+        Some(visit(&node, &mut self.queue))
+    }
+}
+
+impl<'a, M: Monoid> IntoIterator for &'a SegTree<M>
+{
+    type Item = M::Item;
+    type IntoIter = Iter<'a, M>;
+
+    fn into_iter(self) -> Iter<'a, M>
+    {
+        self.iter()
+    }
+}
+
+/// Reads the value at an index like a slice, e.g. `seg_tree[5]`.
+///
+/// # Panics
+///
+/// Panics if the index is out of range.
+impl<M: Monoid> Index<usize> for SegTree<M>
+{
+    type Output = M::Item;
+
+    fn index(&self, pos: usize) -> &M::Item
+    {
+        if !self.contains(pos)
+        {
+            panic!("{}", SegError::OutOfRange { pos });
+        }
+        let mut node = self;
+        loop
+        {
+            let child = if pos < node.mid
+            {
+                &node.l_node
+            }
+            else
+            {
+                &node.r_node
+            };
+            match child
+            {
+                // SAFETY: no node `Rc` ever escapes the tree, so every node
+                // is reachable only through this tree, and nodes are only
+                // borrowed mutably from methods taking `&mut self`, which
+                // cannot run while the returned reference (tied to `&self`)
+                // is alive. A public accessor handing out a node, a node
+                // shared between two trees, or a mutation through `&self`
+                // would break this; `test_index_nodes_unshared` checks the
+                // first two through `max_node_refs`.
+                Some(next) =>
+                {
+                    node = unsafe { next.try_borrow_unguarded() }.expect("node is mutably borrowed")
+                }
+                None => return &node.val,
+            }
+        }
+    }
+}
+
+/// Builds a tree over `[0, data.len())`, panicking on empty input like
+/// [`SegTree::from_slice`].
+impl<M: Monoid> From<Vec<M::Item>> for SegTree<M>
+{
+    fn from(data: Vec<M::Item>) -> SegTree<M>
+    {
+        SegTree::from_slice(&data)
+    }
+}
+
+/// Collects into a tree over `[0, n)`, panicking on empty input like
+/// [`SegTree::from_slice`].
+impl<M: Monoid> FromIterator<M::Item> for SegTree<M>
+{
+    fn from_iter<I: IntoIterator<Item = M::Item>>(iter: I) -> SegTree<M>
+    {
+        SegTree::from_slice(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+/// Applies `(index, value)` point updates as one
+/// [`revise_many`](SegTree::revise_many) batch, panicking like it on an out
+/// of range index.
+impl<M: Monoid> Extend<(usize, M::Item)> for SegTree<M>
+{
+    fn extend<I: IntoIterator<Item = (usize, M::Item)>>(&mut self, iter: I)
+    {
+        self.revise_many(&iter.into_iter().collect::<Vec<_>>());
+    }
+}
+
+/// Prints one node per line, indented by depth, e.g. `node [0, 4): 6`
+/// for internal nodes and `leaf [2, 3): 3` for leaves.
+impl<M: Monoid> fmt::Debug for SegTree<M>
+where
+    M::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        self.fmt_node(f, 0)
+    }
+}
+
+impl<M: Monoid> SegTree<M>
+where
+    M::Item: fmt::Debug,
+{
+    fn fmt_node(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result
+    {
+        let kind = if self.is_leaf() { "leaf" } else { "node" };
+        writeln!(
+            f,
+            "{:indent$}{} [{}, {}): {:?}",
+            "",
+            kind,
+            self.range.0,
+            self.range.1,
+            self.val,
+            indent = 2 * depth
+        )?;
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            child.borrow().fmt_node(f, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the tree as a Graphviz DOT graph, one box per node labelled
+    /// with its range and value. Leaves are filled light blue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2]);
+    /// assert!(seg_tree.to_dot().starts_with("digraph SegTree {"));
+    /// ```
+    pub fn to_dot(&self) -> String
+    {
+        let mut dot = String::from("digraph SegTree {\n    node [shape=box, style=filled];\n");
+        self.dot_node(&mut dot, &mut 0);
+        dot.push_str("}\n");
+        dot
+    }
+
+    // writes this node and its subtree, numbering nodes in pre-order, and
+    // returns this node's number
+    fn dot_node(&self, dot: &mut String, next_id: &mut usize) -> usize
+    {
+        let id = *next_id;
+        *next_id += 1;
+        let label = format!("[{}, {})\n{:?}", self.range.0, self.range.1, self.val);
+        let color = if self.is_leaf()
+        {
+            "lightblue"
+        }
+        else
+        {
+            "lightgray"
+        };
+        // the Debug form of a string is a quoted, escaped DOT string
+        dot.push_str(&format!(
+            "    n{} [label={:?}, fillcolor={}];\n",
+            id, label, color
+        ));
+        for child in self.l_node.iter().chain(self.r_node.iter())
+        {
+            let child_id = child.borrow().dot_node(dot, next_id);
+            dot.push_str(&format!("    n{} -> n{};\n", id, child_id));
+        }
+        id
+    }
+
+    /// Checks that the children of every internal node split its range at
+    /// `mid` and that its value is the combination of theirs, describing
+    /// the first violation found in pre-order.
+    ///
+    /// The whole tree is walked in O(n), so this is meant for tests and
+    /// debugging custom update paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// assert_eq!(seg_tree.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), String>
+    where
+        M::Item: PartialEq,
+    {
+        if self.len() <= 1
+        {
+            return Ok(());
+        }
+        let (Some(left), Some(right)) = (&self.l_node, &self.r_node)
+        else
+        {
+            return Err(format!(
+                "node [{}, {}) is missing a child",
+                self.range.0, self.range.1
+            ));
+        };
+        let (left, right) = (left.borrow(), right.borrow());
+        if left.range != (self.range.0, self.mid) || right.range != (self.mid, self.range.1)
+        {
+            return Err(format!(
+                "node [{}, {}) has children [{}, {}) and [{}, {}), expected a split at {}",
+                self.range.0,
+                self.range.1,
+                left.range.0,
+                left.range.1,
+                right.range.0,
+                right.range.1,
+                self.mid
+            ));
+        }
+        let combined = M::combine(&left.val, &right.val);
+        if self.val != combined
+        {
+            return Err(format!(
+                "node [{}, {}) holds {:?}, but its children combine to {:?}",
+                self.range.0, self.range.1, self.val, combined
+            ));
+        }
+        left.validate()?;
+        right.validate()
+    }
+}
+
+// the on-disk form of a tree: its bounds plus the flat leaf array
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeRepr<T>
+{
+    range: (usize, usize),
+    leaves: Vec<T>,
+}
+
+/// Serializes only the range and the leaf values; the internal nodes are
+/// rebuilt on deserialization.
+#[cfg(feature = "serde")]
+impl<M: Monoid> serde::Serialize for SegTree<M>
+where
+    M::Item: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        SerdeRepr {
+            range: self.range,
+            leaves: self.to_vec(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M: Monoid> serde::Deserialize<'de> for SegTree<M>
+where
+    M::Item: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<SegTree<M>, D::Error>
+    {
+        use serde::de::Error;
+
+        let repr = SerdeRepr::<M::Item>::deserialize(deserializer)?;
+        let (l, r) = repr.range;
+        if l > r || repr.leaves.len() != r - l
+        {
+            return Err(D::Error::custom("leaf count does not match a valid range"));
+        }
+        Ok(Self::build(l, r, &|i| repr.leaves[i - l].clone()))
+    }
+}
+
+impl<T> SegTree<SumMonoid<T>>
+where
+    T: Num + Copy,
+{
+    /// Adds `delta` to the value at a specific index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.point_add(3, 2);
+    /// seg_tree.point_add(3, 2);
+    /// assert_eq!(seg_tree.get(3), 4);
+    /// ```
+    pub fn point_add(&mut self, target_pos: usize, delta: T)
+    {
+        self.checked_update(target_pos, |&value| value + delta)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Flips the value at a specific index between `0` and `1`, for trees
+    /// used as bitsets whose sums count the set bits. Any value other than
+    /// `0` counts as set and becomes `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// seg_tree.toggle(3);
+    /// assert_eq!(seg_tree.total(), 1);
+    /// seg_tree.toggle(3);
+    /// assert_eq!(seg_tree.total(), 0);
+    /// ```
+    pub fn toggle(&mut self, target_pos: usize)
+    {
+        self.checked_update(target_pos, |value| {
+            if value.is_zero()
+            {
+                T::one()
+            }
+            else
+            {
+                T::zero()
+            }
+        })
+        .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Returns the `len() + 1` prefix sums of the leaves, element `i` being
+    /// `ask(range.0, range.0 + i)` and the first one `0`, in one walk.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// assert_eq!(seg_tree.prefix_sums(), vec![0, 1, 3, 6]);
+    /// ```
+    pub fn prefix_sums(&self) -> Vec<T>
+    {
+        let mut sums = Vec::with_capacity(self.len() + 1);
+        sums.push(T::zero());
+        let mut sum = T::zero();
+        for value in self.iter()
+        {
+            sum = sum + value;
+            sums.push(sum);
+        }
+        sums
+    }
+}
+
+impl<T> SegTree<SumMonoid<T>>
+where
+    T: Num + Copy + PartialOrd,
+{
+    /// Returns the smallest index whose prefix sum, counted from the start of
+    /// the range, exceeds `t`, or `range.1` if even the total does not.
+    ///
+    /// With non-negative weights and `t` drawn uniformly from `[0, total)`,
+    /// index `i` is returned with probability proportional to its weight,
+    /// which makes this the lookup step of weighted sampling. Runs in
+    /// O(log n) through [`max_right`](Self::max_right).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 0, 2, 0, 1]);
+    /// assert_eq!(seg_tree.lower_bound(0), 0);
+    /// assert_eq!(seg_tree.lower_bound(1), 2);
+    /// assert_eq!(seg_tree.lower_bound(3), 4);
+    /// ```
+    pub fn lower_bound(&self, t: T) -> usize
+    {
+        self.max_right(self.range.0, |&sum| sum <= t)
+    }
+}
+
+impl SegTree
+{
+    /// Returns the index of the `k`-th one, counting from `1`, in a tree
+    /// holding only zeros and ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is `0` or the tree holds fewer than `k` ones, and if
+    /// the total is negative, which no tree of zeros and ones can have.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let mut seg_tree: SegTree = SegTree::new(0, 10);
+    /// for pos in [2, 5, 7]
+    /// {
+    ///     seg_tree.revise(pos, 1);
+    /// }
+    /// assert_eq!(seg_tree.kth_one(2), 5);
+    /// ```
+    pub fn kth_one(&self, k: usize) -> usize
+    {
+        let ones = usize::try_from(self.total())
+            .unwrap_or_else(|_| panic!("Negative total, the tree must hold only zeros and ones"));
+        if k == 0 || k > ones
+        {
+            panic!("Fewer than k ones in the tree");
+        }
+        self.lower_bound(k as i32 - 1)
+    }
+    /// Encodes the tree as its two bounds, each a little-endian `u64`,
+    /// followed by the leaf values as little-endian `i32`s.
+    ///
+    /// The internal nodes are not stored; `from_bytes` rebuilds them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    /// assert_eq!(seg_tree.to_bytes().len(), 16 + 3 * 4);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut bytes = Vec::with_capacity(16 + 4 * self.len());
+        bytes.extend_from_slice(&(self.range.0 as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.range.1 as u64).to_le_bytes());
+        for value in self.iter()
+        {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+    /// Decodes a tree written by `to_bytes`.
+    ///
+    /// Returns `SegError::InvalidBytes` if the buffer is truncated, has
+    /// trailing bytes, or holds bounds that do not form a valid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::{SegError, SegTree};
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// let bytes = seg_tree.to_bytes();
+    /// assert!(SegTree::from_bytes(&bytes) == Ok(seg_tree));
+    /// assert!(SegTree::from_bytes(&bytes[..20]) == Err(SegError::InvalidBytes));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<SegTree, SegError>
+    {
+        let bound = |at: usize| -> Result<usize, SegError> {
+            let word = bytes
+                .get(at..at + 8)
+                .and_then(|word| word.try_into().ok())
+                .ok_or(SegError::InvalidBytes)?;
+            usize::try_from(u64::from_le_bytes(word)).map_err(|_| SegError::InvalidBytes)
+        };
+        let (l, r) = (bound(0)?, bound(8)?);
+        let leaves = &bytes[16..];
+        if l > r || (r - l).checked_mul(4) != Some(leaves.len())
+        {
+            return Err(SegError::InvalidBytes);
+        }
+        let values: Vec<i32> = leaves
+            .chunks_exact(4)
+            .map(|chunk| i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        Ok(Self::build(l, r, &|i| values[i - l]))
+    }
+}
+
+impl<T> SegTree<SumMonoid<T>>
+where
+    T: Num + Copy + ToPrimitive,
+{
+    /// Returns the average of the values in `[l, r)`.
+    ///
+    /// Every index in the range counts towards the average, so the sum is
+    /// divided by `r - l`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid or empty, as an empty range has
+    /// no average.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree: SegTree = (0..10).collect();
+    /// assert_eq!(seg_tree.ask_avg(0, 10), 4.5);
+    /// ```
+    pub fn ask_avg(&self, l: usize, r: usize) -> f64
+    {
+        if l == r
+        {
+            panic!("{}", SegError::InvalidRange { l, r });
+        }
+        let sum = self
+            .ask(l, r)
+            .to_f64()
+            .expect("Range sum is not representable as f64");
+        sum / (r - l) as f64
+    }
+}
+
+impl SegTree<MinMonoid>
+{
+    /// Creates a new range-minimum segment tree over `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_min(l: usize, r: usize) -> SegTree<MinMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<GcdMonoid>
+{
+    /// Creates a new range-GCD segment tree over `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_gcd(l: usize, r: usize) -> SegTree<GcdMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<OrMonoid>
+{
+    /// Creates a new range bitwise OR segment tree over `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_or(l: usize, r: usize) -> SegTree<OrMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<AndMonoid>
+{
+    /// Creates a new range bitwise AND segment tree over `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_and(l: usize, r: usize) -> SegTree<AndMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<XorMonoid>
+{
+    /// Creates a new range bitwise XOR segment tree over `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_xor(l: usize, r: usize) -> SegTree<XorMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<MaxIndexMonoid>
+{
+    /// Creates a max-with-index tree over `[0, values.len())` holding
+    /// `values`, each leaf storing `(values[i], i)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree = SegTree::new_max_index(&[3, 1, 4, 1, 5]);
+    /// assert_eq!(seg_tree.ask(0, 5), (5, 4));
+    /// ```
+    pub fn new_max_index(values: &[i32]) -> SegTree<MaxIndexMonoid>
+    {
+        if values.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, values.len(), &|i| (values[i], i))
+    }
+    /// Sets the value at a specific index to `value`, storing
+    /// `(value, target_pos)` at its leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise_value(&mut self, target_pos: usize, value: i32)
+    {
+        self.revise(target_pos, (value, target_pos));
+    }
+}
+
+impl SegTree<SecondMaxMonoid>
+{
+    /// Creates a second-maximum tree over `[0, values.len())` holding
+    /// `values`, each leaf storing `(values[i], i32::MIN)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree = SegTree::new_second_max(&[5, 3, 8, 8, 1]);
+    /// assert_eq!(seg_tree.ask(0, 5), (8, 8));
+    /// ```
+    pub fn new_second_max(values: &[i32]) -> SegTree<SecondMaxMonoid>
+    {
+        if values.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, values.len(), &|i| (values[i], i32::MIN))
+    }
+    /// Sets the value at a specific index to `value`, storing
+    /// `(value, i32::MIN)` at its leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise_value(&mut self, target_pos: usize, value: i32)
+    {
+        self.revise(target_pos, (value, i32::MIN));
+    }
+}
+
+impl<const BASE: u64, const MOD: u64> SegTree<PolyHashMonoid<BASE, MOD>>
+{
+    /// Creates a hash tree over the characters of `text`, one leaf per
+    /// `char`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `text` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// # use seg_tree::seg_tree::PolyHashMonoid;
+    /// let seg_tree = SegTree::<PolyHashMonoid<131, 1_000_000_007>>::new_poly_hash("abcabc");
+    /// assert_eq!(seg_tree.ask_hash(0, 3), seg_tree.ask_hash(3, 6));
+    /// assert_ne!(seg_tree.ask_hash(0, 3), seg_tree.ask_hash(1, 4));
+    /// ```
+    pub fn new_poly_hash(text: &str) -> SegTree<PolyHashMonoid<BASE, MOD>>
+    {
+        text.chars()
+            .map(|c| PolyHashMonoid::<BASE, MOD>::leaf(c as u64))
+            .collect()
+    }
+    /// Sets the character at a specific index to the one with code `code`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise_value(&mut self, target_pos: usize, code: u64)
+    {
+        self.revise(target_pos, PolyHashMonoid::<BASE, MOD>::leaf(code));
+    }
+    /// Returns the hash of the substring `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask_hash(&self, l: usize, r: usize) -> u64
+    {
+        self.ask(l, r).0
+    }
+}
+
+impl SegTree<VarianceMonoid>
+{
+    /// Creates a variance tree over `[0, values.len())` holding `values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn from_values(values: &[i64]) -> SegTree<VarianceMonoid>
+    {
+        if values.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, values.len(), &|i| VarianceMonoid::leaf(values[i]))
+    }
+    /// Sets the value at a specific index to `value`, storing
+    /// `(value, value * value)` at its leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise_value(&mut self, target_pos: usize, value: i64)
+    {
+        self.revise(target_pos, VarianceMonoid::leaf(value));
+    }
+    /// Returns the population variance `E[x^2] - E[x]^2` of the values in
+    /// `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid or empty, as an empty range has
+    /// no variance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let values: Vec<i64> = (0..10).collect();
+    /// let seg_tree = SegTree::from_values(&values);
+    /// assert_eq!(seg_tree.ask_variance(0, 10), 8.25);
+    /// ```
+    pub fn ask_variance(&self, l: usize, r: usize) -> f64
+    {
+        if l == r
+        {
+            panic!("{}", SegError::InvalidRange { l, r });
+        }
+        let (sum, sum_sq) = self.ask(l, r);
+        let len = (r - l) as f64;
+        let mean = sum as f64 / len;
+        sum_sq as f64 / len - mean * mean
+    }
+}
+
+impl SegTree<MaxSubarrayMonoid>
+{
+    /// Creates a maximum subarray tree over `[0, values.len())` holding
+    /// `values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn new_max_subarray(values: &[i32]) -> SegTree<MaxSubarrayMonoid>
+    {
+        if values.is_empty()
+        {
+            panic!("{}", SegError::Empty);
+        }
+        Self::build(0, values.len(), &|i| Subarray::leaf(values[i]))
+    }
+    /// Sets the value at a specific index to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise_value(&mut self, target_pos: usize, value: i32)
+    {
+        self.revise(target_pos, Subarray::leaf(value));
+    }
+    /// Returns the largest sum of a non-empty contiguous subarray of
+    /// `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree;
+    /// let seg_tree = SegTree::new_max_subarray(&[-2, 1, -3, 4, -1, 2, 1, -5, 4]);
+    /// assert_eq!(seg_tree.ask_max_subarray(0, 9), 6);
+    /// ```
+    pub fn ask_max_subarray(&self, l: usize, r: usize) -> i32
+    {
+        self.ask(l, r).best
+    }
+}
+
+impl SegTree<SaturatingSumMonoid>
+{
+    /// Creates a new sum tree over `[l, r)` whose sums saturate at the
+    /// bounds of `i32`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_saturating(l: usize, r: usize) -> SegTree<SaturatingSumMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<WrappingSumMonoid>
+{
+    /// Creates a new sum tree over `[l, r)` whose sums wrap around on
+    /// overflow.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_wrapping(l: usize, r: usize) -> SegTree<WrappingSumMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+impl SegTree<CheckedSumMonoid>
+{
+    /// Creates a new sum tree over `[l, r)` that reports overflowing sums,
+    /// every value starting at `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_checked(l: usize, r: usize) -> SegTree<CheckedSumMonoid>
+    {
+        Self::new(l, r)
+    }
+    /// Sets the value at a specific index to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise_value(&mut self, target_pos: usize, value: i32)
+    {
+        self.revise(target_pos, Some(value));
+    }
+    /// Queries the sum of `[l, r)`, returning [`SegError::Overflow`] if it
+    /// does not fit in an `i32`.
+    ///
+    /// An overflow inside a node counts even when the whole range would sum
+    /// back into bounds, e.g. `[i32::MAX, 1, -1]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::{SegError, SegTree};
+    /// let mut seg_tree = SegTree::new_checked(0, 2);
+    /// seg_tree.revise_value(0, i32::MAX);
+    /// assert_eq!(seg_tree.ask_checked(0, 1), Ok(i32::MAX));
+    /// seg_tree.revise_value(1, 1);
+    /// assert_eq!(seg_tree.ask_checked(0, 2), Err(SegError::Overflow { l: 0, r: 2 }));
+    /// ```
+    pub fn ask_checked(&self, l: usize, r: usize) -> Result<i32, SegError>
+    {
+        self.try_ask(l, r)?.ok_or(SegError::Overflow { l, r })
+    }
+}
+
+impl SegTree<MaxMonoid>
+{
+    /// Creates a new range-maximum segment tree over `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new_max(l: usize, r: usize) -> SegTree<MaxMonoid>
+    {
+        Self::new(l, r)
+    }
+}
+
+/// Counts the distinct values in `data[l..r]` for every `(l, r)` in
+/// `queries`, answering them all offline in O((n + q) log n).
+///
+/// The queries are swept by right endpoint while a sum tree marks, for every
+/// value seen so far, only its last occurrence with a `1`. The number of
+/// distinct values in `[l, r)` is then the number of marks in `[l, r)`.
+///
+/// # Panics
+///
+/// Panics if a query range is invalid.
+///
+/// # Examples
+///
+/// ```
+/// # use seg_tree::offline_distinct;
+/// let data = [1, 2, 1, 3, 2];
+/// assert_eq!(offline_distinct(&data, &[(0, 5), (1, 3), (2, 2)]), vec![3, 2, 0]);
+/// ```
+pub fn offline_distinct(data: &[i32], queries: &[(usize, usize)]) -> Vec<usize>
+{
+    let mut order: Vec<usize> = (0..queries.len()).collect();
+    order.sort_unstable_by_key(|&i| queries[i].1);
+    let mut marks: SegTree = SegTree::new(0, data.len());
+    let mut last = BTreeMap::new();
+    let mut answers = vec![0; queries.len()];
+    let mut swept = 0;
+    for i in order
+    {
+        let (l, r) = queries[i];
+        if !marks.contains_range(l, r)
+        {
+            panic!("{}", SegError::InvalidRange { l, r });
+        }
+        while swept < r
+        {
+            if let Some(prev) = last.insert(data[swept], swept)
+            {
+                marks.point_add(prev, -1);
+            }
+            marks.point_add(swept, 1);
+            swept += 1;
+        }
+        answers[i] = marks.ask(l, r) as usize;
+    }
+    answers
+}
+
+/// Finds the most frequent value in `data[l..r]` for every `(l, r)` in
+/// `queries`, ties going to the smallest value.
+///
+/// This uses sqrt decomposition: with blocks of about `sqrt(n)` elements,
+/// the modes of all runs of whole blocks are precomputed in O(n sqrt(n)).
+/// A query starts from the mode of the whole blocks it covers, and only the
+/// at most `2 * sqrt(n)` elements left over at its ends can beat it; their
+/// counts over the query range are found by binary search in the sorted
+/// positions of each value. Each query thus takes O(sqrt(n) log n).
+///
+/// # Panics
+///
+/// Panics if a query range is empty or invalid, since it has no mode.
+///
+/// # Examples
+///
+/// ```
+/// # use seg_tree::offline_mode;
+/// let data = [3, 1, 3, 2, 1, 1];
+/// assert_eq!(offline_mode(&data, &[(0, 6), (0, 4), (1, 4)]), vec![1, 3, 1]);
+/// ```
+pub fn offline_mode(data: &[i32], queries: &[(usize, usize)]) -> Vec<i32>
+{
+    let n = data.len();
+    let mut values = data.to_vec();
+    values.sort_unstable();
+    values.dedup();
+    // smaller values get smaller ids, so ties can be broken on the id
+    let ids: Vec<usize> = data
+        .iter()
+        .map(|value| values.partition_point(|v| v < value))
+        .collect();
+    let mut positions = vec![Vec::new(); values.len()];
+    for (i, &id) in ids.iter().enumerate()
+    {
+        positions[id].push(i);
+    }
+    // `(id, count)` pairs, comparing higher counts, then smaller ids, first
+    let better = |id: usize, count: usize, best: (usize, usize)| {
+        count > best.1 || (count == best.1 && id < best.0)
+    };
+
+    let block = n.isqrt().max(1);
+    let blocks = n.div_ceil(block);
+    // block_modes[a * blocks + b] is the mode of blocks `a..=b`
+    let mut block_modes = vec![(0, 0); blocks * blocks];
+    let mut counts = vec![0; values.len()];
+    for a in 0..blocks
+    {
+        counts.fill(0);
+        let mut best = (0, 0);
+        for (i, &id) in ids.iter().enumerate().skip(a * block)
+        {
+            counts[id] += 1;
+            if better(id, counts[id], best)
+            {
+                best = (id, counts[id]);
+            }
+            if (i + 1) % block == 0 || i + 1 == n
+            {
+                block_modes[a * blocks + i / block] = best;
+            }
+        }
+    }
+
+    queries
+        .iter()
+        .map(|&(l, r)| {
+            if l >= r || r > n
+            {
+                panic!("{}", SegError::InvalidRange { l, r });
+            }
+            // whole blocks `first..last` lie inside `[l, r)`
+            let (first, last) = (l.div_ceil(block), r / block);
+            let (mut best, ends) = if first < last
+            {
+                (
+                    block_modes[first * blocks + last - 1],
+                    (l..first * block).chain(last * block..r),
+                )
+            }
+            else
+            {
+                ((0, 0), (l..r).chain(0..0))
+            };
+            for i in ends
+            {
+                let id = ids[i];
+                let count = positions[id].partition_point(|&pos| pos < r)
+                    - positions[id].partition_point(|&pos| pos < l);
+                if better(id, count, best)
+                {
+                    best = (id, count);
+                }
+            }
+            values[best.0]
+        })
+        .collect()
+}
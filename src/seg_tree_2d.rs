@@ -0,0 +1,169 @@
+use alloc::boxed::Box;
+
+use super::seg_tree::{Monoid, SegError, SegTree, SumMonoid};
+
+/// A segment tree over the rows of a grid whose nodes each hold a
+/// [`SegTree`] over the columns, answering rectangle queries in
+/// O(log rows * log cols).
+///
+/// The inner tree of an outer node covering rows `[a, b)` holds, for every
+/// column, the combined value of that column over those rows. Each row is
+/// covered by one node per level, so the grid takes O(rows * cols) memory.
+///
+/// Each node combines its rows column by column before a query combines
+/// the columns, so `M` should be commutative, as sums, minimums and
+/// maximums are.
+pub struct SegTree2D<M: Monoid = SumMonoid>
+{
+    inner: SegTree<M>,
+    rows: (usize, usize),
+    mid: usize,
+    l_node: Option<Box<SegTree2D<M>>>,
+    r_node: Option<Box<SegTree2D<M>>>,
+}
+
+impl<M: Monoid> SegTree2D<M>
+{
+    /// Creates a grid of `rows` by `cols` cells, every cell holding the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rows` or `cols` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree2D;
+    /// let grid: SegTree2D = SegTree2D::new(4, 4);
+    /// ```
+    pub fn new(rows: usize, cols: usize) -> SegTree2D<M>
+    {
+        if rows == 0 || cols == 0
+        {
+            panic!("Invalid size: a grid needs at least one row and one column");
+        }
+        Self::build(0, rows, cols)
+    }
+
+    fn build(l_bound: usize, r_bound: usize, cols: usize) -> SegTree2D<M>
+    {
+        let mid = (l_bound + r_bound) / 2;
+        let (l_node, r_node) = if r_bound - l_bound == 1
+        {
+            (None, None)
+        }
+        else
+        {
+            (
+                Some(Box::new(Self::build(l_bound, mid, cols))),
+                Some(Box::new(Self::build(mid, r_bound, cols))),
+            )
+        };
+        SegTree2D {
+            inner: SegTree::new(0, cols),
+            rows: (l_bound, r_bound),
+            mid,
+            l_node,
+            r_node,
+        }
+    }
+    /// Updates the cell at row `row` and column `col`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell lies outside the grid.
+    pub fn revise(&mut self, row: usize, col: usize, value: M::Item)
+    {
+        if row >= self.rows.1
+        {
+            panic!("{}", SegError::OutOfRange { pos: row });
+        }
+        if col >= self.inner.len()
+        {
+            panic!("{}", SegError::OutOfRange { pos: col });
+        }
+        self.update(row, col, value);
+    }
+
+    fn update(&mut self, row: usize, col: usize, value: M::Item)
+    {
+        if (row, row + 1) == self.rows
+        {
+            self.inner.revise(col, value);
+            return;
+        }
+        let child = if row < self.mid
+        {
+            &mut self.l_node
+        }
+        else
+        {
+            &mut self.r_node
+        };
+        if let Some(node) = child
+        {
+            node.update(row, col, value);
+        }
+        // only the column that changed needs recombining
+        let column = |child: &Option<Box<SegTree2D<M>>>| {
+            child
+                .as_ref()
+                .map_or(M::identity(), |node| node.inner.get(col))
+        };
+        let combined = M::combine(&column(&self.l_node), &column(&self.r_node));
+        self.inner.revise(col, combined);
+    }
+    /// Queries the combined value of the rectangle of rows `[r1, r2)` and
+    /// columns `[c1, c2)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTree2D;
+    /// let mut grid: SegTree2D = SegTree2D::new(4, 4);
+    /// grid.revise(1, 2, 5);
+    /// grid.revise(3, 0, 2);
+    /// assert_eq!(grid.ask(0, 0, 4, 4), 7);
+    /// assert_eq!(grid.ask(0, 1, 2, 3), 5);
+    /// ```
+    #[must_use]
+    pub fn ask(&self, r1: usize, c1: usize, r2: usize, c2: usize) -> M::Item
+    {
+        if r1 >= r2 || r2 > self.rows.1 || c1 >= c2 || c2 > self.inner.len()
+        {
+            panic!("Invalid query range");
+        }
+        self.query(r1, r2, c1, c2)
+    }
+
+    fn query(&self, r1: usize, r2: usize, c1: usize, c2: usize) -> M::Item
+    {
+        if (r1, r2) == self.rows
+        {
+            return self.inner.ask(c1, c2);
+        }
+        let top = match &self.l_node
+        {
+            Some(left) if r1 < self.mid => left.query(r1, r2.min(self.mid), c1, c2),
+            _ => M::identity(),
+        };
+        let bottom = match &self.r_node
+        {
+            Some(right) if r2 > self.mid => right.query(r1.max(self.mid), r2, c1, c2),
+            _ => M::identity(),
+        };
+        M::combine(&top, &bottom)
+    }
+
+    /// Returns the number of rows and columns.
+    #[must_use]
+    pub fn dims(&self) -> (usize, usize)
+    {
+        (self.rows.1, self.inner.len())
+    }
+}
@@ -0,0 +1,132 @@
+use super::bottom_up;
+use super::seg_tree::{Monoid, SegError, SumMonoid};
+
+/// A segment tree over `[0, N)` stored inline in two arrays of `N` values,
+/// without any heap allocation.
+///
+/// Node `i` sits at `internal[i]` for `i < N` and at `leaves[i - N]`
+/// otherwise, with the children of node `i` at `2 * i` and `2 * i + 1`
+/// like in [`ArraySegTree`]. Stable Rust cannot size a single array as
+/// `2 * N.next_power_of_two()`, so the leaves are not padded: `revise` and
+/// `ask` walk bottom-up over the `2 * N` nodes, which stays correct for any
+/// `N`, but the root no longer holds the total unless `N` is a power of
+/// two.
+///
+/// [`ArraySegTree`]: super::array_seg_tree::ArraySegTree
+pub struct SegTreeN<const N: usize, M: Monoid = SumMonoid>
+{
+    internal: [M::Item; N],
+    leaves: [M::Item; N],
+}
+
+impl<const N: usize, M: Monoid> SegTreeN<N, M>
+{
+    /// Creates a new inline segment tree over `[0, N)`, every value
+    /// starting as the identity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTreeN;
+    /// let seg_tree: SegTreeN<8> = SegTreeN::new();
+    /// assert_eq!(seg_tree.ask(0, 8), 0);
+    /// ```
+    pub fn new() -> SegTreeN<N, M>
+    {
+        SegTreeN {
+            internal: core::array::from_fn(|_| M::identity()),
+            leaves: core::array::from_fn(|_| M::identity()),
+        }
+    }
+    /// Updates the value at a specific index in the segment tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SegTreeN;
+    /// let mut seg_tree: SegTreeN<8> = SegTreeN::new();
+    /// seg_tree.revise(3, 5);
+    /// assert_eq!(seg_tree.ask(0, 4), 5);
+    /// ```
+    pub fn revise(&mut self, target_pos: usize, value: M::Item)
+    {
+        self.try_revise(target_pos, value)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Updates the value at a specific index, returning an error instead
+    /// of panicking if the index is out of range.
+    pub fn try_revise(&mut self, target_pos: usize, value: M::Item) -> Result<(), SegError>
+    {
+        if target_pos >= N
+        {
+            return Err(SegError::OutOfRange { pos: target_pos });
+        }
+        self.leaves[target_pos] = value;
+        bottom_up::pull_path(N + target_pos, |node| {
+            self.internal[node] = M::combine(self.node(2 * node), self.node(2 * node + 1));
+        });
+        Ok(())
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        self.try_ask(l, r).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Queries the combined value of `[l, r)`, returning an error instead
+    /// of panicking if the query range is invalid.
+    pub fn try_ask(&self, l: usize, r: usize) -> Result<M::Item, SegError>
+    {
+        if l > r || r > N
+        {
+            return Err(SegError::InvalidRange { l, r });
+        }
+        // without padding, a range can end in a leaf and an internal node
+        // at the same level, which `node` tells apart
+        Ok(bottom_up::fold(
+            N + l,
+            N + r,
+            M::identity,
+            |node| self.node(node),
+            M::combine,
+        ))
+    }
+    /// Returns the number of elements covered by the tree, `N`.
+    pub fn len(&self) -> usize
+    {
+        N
+    }
+    /// Returns `true` if the tree covers no elements.
+    pub fn is_empty(&self) -> bool
+    {
+        N == 0
+    }
+
+    fn node(&self, node: usize) -> &M::Item
+    {
+        if node < N
+        {
+            &self.internal[node]
+        }
+        else
+        {
+            &self.leaves[node - N]
+        }
+    }
+}
+
+impl<const N: usize, M: Monoid> Default for SegTreeN<N, M>
+{
+    fn default() -> SegTreeN<N, M>
+    {
+        Self::new()
+    }
+}
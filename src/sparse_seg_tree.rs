@@ -0,0 +1,177 @@
+use alloc::boxed::Box;
+
+use super::seg_tree::{Monoid, SegError, SumMonoid};
+
+/// A segment tree over a huge range that only allocates the nodes on the
+/// paths to revised indices.
+///
+/// A missing child stands for a subtree of identities, so `new(0, 1 <<
+/// 30)` allocates a single node and each `revise` adds at most one node
+/// per level.
+pub struct SparseSegTree<M: Monoid = SumMonoid>
+{
+    val: M::Item,
+    range: (usize, usize),
+    mid: usize,
+    l_node: Option<Box<SparseSegTree<M>>>,
+    r_node: Option<Box<SparseSegTree<M>>>,
+}
+
+impl<M: Monoid> SparseSegTree<M>
+{
+    /// Creates a new sparse segment tree with the specified range `[l, r)`,
+    /// every value starting as the identity.
+    ///
+    /// `l == r` gives an empty tree, whose asks over `[l, l)` return the
+    /// identity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SparseSegTree;
+    /// let seg_tree: SparseSegTree = SparseSegTree::new(0, 1 << 30);
+    /// ```
+    pub fn new(l: usize, r: usize) -> SparseSegTree<M>
+    {
+        if l > r
+        {
+            panic!("Invalid range: left bound must not exceed right bound");
+        }
+        SparseSegTree {
+            val: M::identity(),
+            range: (l, r),
+            mid: (l + r) / 2,
+            l_node: None,
+            r_node: None,
+        }
+    }
+    /// Updates the value at a specific index, creating the nodes on its
+    /// path that do not exist yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: M::Item)
+    {
+        self.try_revise(target_pos, value)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+    /// Updates the value at a specific index, returning an error instead
+    /// of panicking if the index is out of range.
+    pub fn try_revise(&mut self, target_pos: usize, value: M::Item) -> Result<(), SegError>
+    {
+        if target_pos < self.range.0 || target_pos >= self.range.1
+        {
+            return Err(SegError::OutOfRange { pos: target_pos });
+        }
+        self.update(target_pos, value);
+        Ok(())
+    }
+
+    fn update(&mut self, target_pos: usize, value: M::Item)
+    {
+        if (target_pos, target_pos + 1) == self.range
+        {
+            self.val = value;
+            return;
+        }
+        let (l_bound, mid, r_bound) = (self.range.0, self.mid, self.range.1);
+        if target_pos < mid
+        {
+            self.l_node
+                .get_or_insert_with(|| Box::new(Self::new(l_bound, mid)))
+                .update(target_pos, value);
+        }
+        else
+        {
+            self.r_node
+                .get_or_insert_with(|| Box::new(Self::new(mid, r_bound)))
+                .update(target_pos, value);
+        }
+        self.val = M::combine(
+            &Self::child_val(&self.l_node),
+            &Self::child_val(&self.r_node),
+        );
+    }
+
+    fn child_val(child: &Option<Box<SparseSegTree<M>>>) -> M::Item
+    {
+        child
+            .as_ref()
+            .map_or(M::identity(), |node| node.val.clone())
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::SparseSegTree;
+    /// let mut seg_tree: SparseSegTree = SparseSegTree::new(0, 1 << 30);
+    /// seg_tree.revise(1 << 29, 5);
+    /// assert_eq!(seg_tree.ask(0, 1 << 30), 5);
+    /// ```
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        self.try_ask(l, r).unwrap_or_else(|err| panic!("{}", err))
+    }
+    /// Queries the combined value of `[l, r)`, returning an error instead
+    /// of panicking if the query range is invalid.
+    pub fn try_ask(&self, l: usize, r: usize) -> Result<M::Item, SegError>
+    {
+        if l > r || l < self.range.0 || r > self.range.1
+        {
+            return Err(SegError::InvalidRange { l, r });
+        }
+        if l == r
+        {
+            return Ok(M::identity());
+        }
+        Ok(self.query(l, r))
+    }
+
+    fn query(&self, l: usize, r: usize) -> M::Item
+    {
+        if (l, r) == self.range
+        {
+            return self.val.clone();
+        }
+        let left_val = match &self.l_node
+        {
+            Some(left) if l < self.mid => left.query(l, r.min(self.mid)),
+            _ => M::identity(),
+        };
+        let right_val = match &self.r_node
+        {
+            Some(right) if r > self.mid => right.query(l.max(self.mid), r),
+            _ => M::identity(),
+        };
+        M::combine(&left_val, &right_val)
+    }
+    /// Returns the number of nodes allocated so far.
+    pub fn node_count(&self) -> usize
+    {
+        1 + self.l_node.as_ref().map_or(0, |left| left.node_count())
+            + self.r_node.as_ref().map_or(0, |right| right.node_count())
+    }
+
+    // for testing
+    #[must_use]
+    pub fn get_val(&self) -> M::Item
+    {
+        self.val.clone()
+    }
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.range
+    }
+}
@@ -0,0 +1,2682 @@
+use super::array_seg_tree::ArraySegTree;
+use super::beats_seg_tree::BeatsSegTree;
+#[cfg(feature = "std")]
+use super::concurrent_seg_tree::ConcurrentSegTree;
+use super::fn_seg_tree::FnSegTree;
+use super::lazy_seg_tree::{
+    AddMin, AddSum, AffineSum, AssignAddSum, AssignSum, LazySegTree, ProgressionSum,
+};
+use super::merge_sort_tree::MergeSortTree;
+use super::persistent_seg_tree::PersistentSegTree;
+use super::seg_tree::{
+    offline_distinct, offline_mode, AndMonoid, GcdMonoid, Matrix2, Matrix2Monoid, MinMonoid,
+    ModInt, ModSumMonoid, Monoid, PolyHashMonoid, SegError, SegTree, SumMonoid,
+};
+use super::seg_tree_2d::SegTree2D;
+use super::seg_tree_n::SegTreeN;
+use super::sparse_seg_tree::SparseSegTree;
+use super::threshold_seg_tree::ThresholdSegTree;
+use super::undo_seg_tree::UndoSegTree;
+
+#[test]
+fn test_build()
+{
+    let seg_tree = SegTree::<SumMonoid>::new(0, 10);
+    assert_eq!(seg_tree.get_range(), (0, 10));
+    assert_eq!(seg_tree.get_val(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid range: left bound must not exceed right bound")]
+fn test_invalid_build()
+{
+    SegTree::<SumMonoid>::new(10, 0);
+}
+
+#[test]
+fn test_revise()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.revise(2, 10);
+    assert_eq!(seg_tree.ask(2, 3), 10);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_invalid_revise()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.revise(10, 10);
+}
+
+#[test]
+fn test_ask()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    for i in 0..10
+    {
+        seg_tree.revise(i, i as i32);
+    }
+    assert_eq!(seg_tree.ask(0, 10), 45); // Sum of 0 to 9
+    assert_eq!(seg_tree.ask(0, 5), 10); // Sum of 0 to 4
+    assert_eq!(seg_tree.ask(5, 10), 35); // Sum of 5 to 9
+    assert_eq!(seg_tree.ask(3, 7), 18); // Sum of 3 to 6
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range")]
+fn test_invalid_ask()
+{
+    let seg_tree = SegTree::<SumMonoid>::new(0, 10);
+    let _ = seg_tree.ask(10, 0);
+}
+
+#[test]
+fn test_i64_no_overflow()
+{
+    let mut seg_tree = SegTree::<SumMonoid<i64>>::new(0, 1_000_000);
+    for i in 0..4
+    {
+        seg_tree.revise(i * 250_000, 2_000_000_000);
+    }
+    assert_eq!(seg_tree.ask(0, 1_000_000), 8_000_000_000);
+    assert_eq!(seg_tree.ask(250_000, 750_000), 4_000_000_000);
+}
+
+#[test]
+fn test_f64()
+{
+    let mut seg_tree = SegTree::<SumMonoid<f64>>::new(0, 4);
+    seg_tree.revise(1, 0.5);
+    seg_tree.revise(3, 1.25);
+    assert_eq!(seg_tree.ask(0, 4), 1.75);
+}
+
+struct ProductMonoid;
+
+impl Monoid for ProductMonoid
+{
+    type Item = i64;
+    fn identity() -> i64
+    {
+        1
+    }
+    fn combine(a: &i64, b: &i64) -> i64
+    {
+        a * b
+    }
+}
+
+#[test]
+fn test_custom_monoid()
+{
+    let mut seg_tree = SegTree::<ProductMonoid>::new(0, 10);
+    for i in 0..10
+    {
+        seg_tree.revise(i, i as i64 + 1);
+    }
+    assert_eq!(seg_tree.ask(0, 10), 3_628_800);
+    assert_eq!(seg_tree.ask(2, 5), 60);
+    assert_eq!(seg_tree.ask(9, 10), 10);
+}
+
+#[test]
+fn test_min_max()
+{
+    let mut min_tree = SegTree::new_min(0, 10);
+    let mut max_tree = SegTree::new_max(0, 10);
+    assert_eq!(min_tree.ask(0, 10), i32::MAX);
+    assert_eq!(max_tree.ask(0, 10), i32::MIN);
+    for i in 0..10
+    {
+        min_tree.revise(i, i as i32);
+        max_tree.revise(i, i as i32);
+    }
+    assert_eq!(min_tree.ask(3, 7), 3);
+    assert_eq!(max_tree.ask(3, 7), 6);
+    min_tree.revise(5, -1);
+    assert_eq!(min_tree.ask(3, 7), -1);
+}
+
+#[test]
+fn test_try_revise()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    assert_eq!(seg_tree.try_revise(3, 7), Ok(()));
+    assert_eq!(seg_tree.ask(3, 4), 7);
+    assert_eq!(
+        seg_tree.try_revise(10, 1),
+        Err(SegError::OutOfRange { pos: 10 })
+    );
+}
+
+#[test]
+fn test_try_ask()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.revise(4, 2);
+    assert_eq!(seg_tree.try_ask(0, 10), Ok(2));
+    assert_eq!(
+        seg_tree.try_ask(10, 0),
+        Err(SegError::InvalidRange { l: 10, r: 0 })
+    );
+    assert_eq!(
+        seg_tree.try_ask(5, 11),
+        Err(SegError::InvalidRange { l: 5, r: 11 })
+    );
+}
+
+#[test]
+fn test_from_slice()
+{
+    let data = [5, -3, 8, 0, 2, 7, -1];
+    let built: SegTree = SegTree::from_slice(&data);
+    let mut revised: SegTree = SegTree::new(0, data.len());
+    for (i, &v) in data.iter().enumerate()
+    {
+        revised.revise(i, v);
+    }
+    assert_eq!(built.get_range(), (0, data.len()));
+    for l in 0..data.len()
+    {
+        for r in l + 1..=data.len()
+        {
+            assert_eq!(built.ask(l, r), revised.ask(l, r));
+        }
+    }
+}
+
+#[test]
+fn test_from_slice_single()
+{
+    let mut seg_tree: SegTree = SegTree::from_slice(&[4]);
+    assert_eq!(seg_tree.ask(0, 1), 4);
+    seg_tree.revise(0, 9);
+    assert_eq!(seg_tree.get_val(), 9);
+}
+
+#[test]
+#[should_panic(expected = "Cannot build a tree from empty data")]
+fn test_from_empty_slice()
+{
+    SegTree::<SumMonoid>::from_slice(&[]);
+}
+
+#[test]
+fn test_range_add()
+{
+    let mut seg_tree = LazySegTree::<AddSum>::from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    let before = seg_tree.ask(0, 10);
+    seg_tree.range_add(2, 5, 3);
+    assert_eq!(seg_tree.ask(0, 10), before + 9);
+    assert_eq!(seg_tree.ask(2, 3), 5);
+    assert_eq!(seg_tree.ask(4, 6), 7 + 5);
+    assert_eq!(seg_tree.ask(5, 10), 35);
+}
+
+#[test]
+fn test_range_add_overlapping()
+{
+    let mut seg_tree = LazySegTree::<AddSum>::new(0, 10);
+    let mut brute = [0; 10];
+    for &(l, r, delta) in &[(0, 10, 1), (3, 7, 2), (5, 6, -4), (1, 9, 3), (6, 10, 5)]
+    {
+        seg_tree.range_add(l, r, delta);
+        brute[l..r].iter_mut().for_each(|x| *x += delta);
+    }
+    seg_tree.revise(4, 100);
+    brute[4] = 100;
+    for l in 0..10
+    {
+        for r in l + 1..=10
+        {
+            assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>());
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Invalid update range")]
+fn test_invalid_range_add()
+{
+    let mut seg_tree = LazySegTree::<AddSum>::new(0, 10);
+    seg_tree.range_add(5, 11, 1);
+}
+
+#[test]
+fn test_range_assign()
+{
+    let mut seg_tree = LazySegTree::<AssignSum>::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    seg_tree.range_assign(0, 10, 5);
+    assert_eq!(seg_tree.ask(0, 10), 50);
+    assert_eq!(seg_tree.ask(3, 4), 5);
+    seg_tree.range_assign(2, 7, -1);
+    seg_tree.range_assign(4, 5, 8);
+    assert_eq!(seg_tree.ask(0, 10), 5 + 5 - 1 - 1 + 8 - 1 - 1 + 5 + 5 + 5);
+    assert_eq!(seg_tree.ask(3, 6), 6);
+}
+
+#[test]
+fn test_range_assign_overlapping()
+{
+    let mut seg_tree = LazySegTree::<AssignSum>::new(0, 10);
+    let mut brute = [0; 10];
+    for &(l, r, value) in &[
+        (0, 8, 2),
+        (3, 10, 7),
+        (1, 4, -3),
+        (5, 6, 0),
+        (2, 9, 4),
+        (8, 9, 1),
+    ]
+    {
+        seg_tree.range_assign(l, r, value);
+        brute[l..r].iter_mut().for_each(|x| *x = value);
+    }
+    for l in 0..10
+    {
+        for r in l + 1..=10
+        {
+            assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>());
+        }
+    }
+}
+
+#[test]
+fn test_get()
+{
+    let mut seg_tree: SegTree = SegTree::from_slice(&[4, 8, 15, 16, 23, 42, 1, 2, 3]);
+    seg_tree.revise(7, -5);
+    assert_eq!(seg_tree.get(7), -5);
+    assert_eq!(seg_tree.get(0), 4);
+    assert_eq!(seg_tree.get(8), 3);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range: 10")]
+fn test_invalid_get()
+{
+    let seg_tree: SegTree = SegTree::new(0, 10);
+    let _ = seg_tree.get(10);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range: 3")]
+fn test_beats_invalid_revise()
+{
+    let mut seg_tree = BeatsSegTree::from_slice(&[5, 1, 4]);
+    seg_tree.revise(3, 0);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range: 7")]
+fn test_lazy_invalid_revise()
+{
+    let mut seg_tree = LazySegTree::<AddSum>::new(0, 5);
+    seg_tree.revise(7, 0);
+}
+
+#[test]
+fn test_replace()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.revise(4, 10);
+    assert_eq!(seg_tree.replace(4, 20), 10);
+    assert_eq!(seg_tree.ask(4, 5), 20);
+    assert_eq!(seg_tree.replace(0, 1), 0);
+    assert_eq!(seg_tree.ask(0, 10), 21);
+}
+
+#[test]
+fn test_array_build()
+{
+    let seg_tree = ArraySegTree::<SumMonoid>::new(0, 10);
+    assert_eq!(seg_tree.get_range(), (0, 10));
+    assert_eq!(seg_tree.get_val(), 0);
+}
+
+#[test]
+fn test_array_revise_ask()
+{
+    let mut seg_tree: ArraySegTree = ArraySegTree::new(0, 10);
+    for i in 0..10
+    {
+        seg_tree.revise(i, i as i32);
+    }
+    assert_eq!(seg_tree.ask(0, 10), 45);
+    assert_eq!(seg_tree.ask(0, 5), 10);
+    assert_eq!(seg_tree.ask(5, 10), 35);
+    assert_eq!(seg_tree.ask(3, 7), 18);
+    assert_eq!(seg_tree.ask(9, 10), 9);
+}
+
+#[test]
+fn test_array_parity()
+{
+    let data = [7, -2, 9, 4, 4, 0, -8, 3, 1, 6, 5];
+    let mut array_tree = ArraySegTree::<MinMonoid>::from_slice(&data);
+    let mut node_tree = SegTree::<MinMonoid>::from_slice(&data);
+    array_tree.revise(3, -10);
+    node_tree.revise(3, -10);
+    for l in 0..data.len()
+    {
+        for r in l + 1..=data.len()
+        {
+            assert_eq!(array_tree.ask(l, r), node_tree.ask(l, r));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_array_invalid_revise()
+{
+    let mut seg_tree: ArraySegTree = ArraySegTree::new(0, 10);
+    seg_tree.revise(10, 10);
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range")]
+fn test_array_invalid_ask()
+{
+    let seg_tree: ArraySegTree = ArraySegTree::new(0, 10);
+    let _ = seg_tree.ask(10, 0);
+}
+
+// a small xorshift generator, enough for randomized tests
+fn next_rand(state: &mut u64) -> u64
+{
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+#[test]
+fn test_array_random_ops()
+{
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    let (l_bound, r_bound) = (3, 40);
+    let mut array_tree: ArraySegTree = ArraySegTree::new(l_bound, r_bound);
+    let mut node_tree: SegTree = SegTree::new(l_bound, r_bound);
+    for _ in 0..2000
+    {
+        let a = l_bound + next_rand(&mut state) as usize % (r_bound - l_bound);
+        let b = l_bound + next_rand(&mut state) as usize % (r_bound - l_bound);
+        if next_rand(&mut state).is_multiple_of(2)
+        {
+            let value = (next_rand(&mut state) % 200) as i32 - 100;
+            array_tree.revise(a, value);
+            node_tree.revise(a, value);
+        }
+        else
+        {
+            let (l, r) = (a.min(b), a.max(b) + 1);
+            assert_eq!(array_tree.ask(l, r), node_tree.ask(l, r));
+        }
+    }
+}
+
+#[test]
+fn test_array_huge_range()
+{
+    let mut seg_tree = ArraySegTree::<SumMonoid<u8>>::new(0, 1 << 26);
+    seg_tree.revise(12_345_678, 3);
+    seg_tree.revise((1 << 26) - 1, 4);
+    assert_eq!(seg_tree.ask(0, 1 << 26), 7);
+    assert_eq!(seg_tree.ask(12_345_679, 1 << 26), 4);
+}
+
+#[test]
+fn test_clone()
+{
+    let original: SegTree = SegTree::from_slice(&[1, 2, 3, 4, 5]);
+    let mut copy = original.clone();
+    copy.revise(2, 100);
+    copy.revise(4, -5);
+    assert_eq!(original.ask(0, 5), 15);
+    assert_eq!(original.get(2), 3);
+    assert_eq!(copy.ask(0, 5), 1 + 2 + 100 + 4 - 5);
+}
+
+#[test]
+fn test_debug()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    assert_eq!(
+        format!("{:?}", seg_tree),
+        "node [0, 3): 6\n  leaf [0, 1): 1\n  node [1, 3): 5\n    leaf [1, 2): 2\n    leaf [2, 3): 3\n"
+    );
+}
+
+#[test]
+fn test_to_vec()
+{
+    let data = vec![9, -4, 0, 17, 3, 3, 8, -1, 5, 2, 11, 6, 7];
+    let mut seg_tree: SegTree = SegTree::from_slice(&data);
+    assert_eq!(seg_tree.to_vec(), data);
+    seg_tree.revise(5, 42);
+    assert_eq!(seg_tree.to_vec()[5], 42);
+    assert_eq!(SegTree::<SumMonoid>::new(2, 6).to_vec(), vec![0; 4]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip()
+{
+    let mut seg_tree: SegTree = SegTree::new(2, 9);
+    for i in 2..9
+    {
+        seg_tree.revise(i, (i * i) as i32 - 20);
+    }
+    let json = serde_json::to_string(&seg_tree).unwrap();
+    let restored: SegTree = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.get_range(), (2, 9));
+    for l in 2..9
+    {
+        for r in l + 1..=9
+        {
+            assert_eq!(restored.ask(l, r), seg_tree.ask(l, r));
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_rejects_mismatched_leaves()
+{
+    let result: Result<SegTree, _> = serde_json::from_str(r#"{"range":[0,3],"leaves":[1,2]}"#);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_persistent_versions()
+{
+    let updates = [(1, 5), (7, 2), (3, -4), (1, 9), (9, 6)];
+    let mut versions: Vec<PersistentSegTree> = vec![PersistentSegTree::new(0, 10)];
+    for &(pos, value) in &updates
+    {
+        let next = versions.last().unwrap().revise(pos, value);
+        versions.push(next);
+    }
+    let mut brute = [0; 10];
+    for (version, &(pos, value)) in updates.iter().enumerate()
+    {
+        brute[pos] = value;
+        for l in 0..10
+        {
+            for r in l + 1..=10
+            {
+                assert_eq!(
+                    versions[version + 1].ask(l, r),
+                    brute[l..r].iter().sum::<i32>()
+                );
+            }
+        }
+    }
+    assert_eq!(versions[0].ask(0, 10), 0);
+    assert_eq!(versions[3].ask(0, 10), 5 + 2 - 4);
+    assert_eq!(versions[3].ask(1, 2), 5);
+}
+
+#[test]
+fn test_max_right()
+{
+    let data: Vec<i32> = (0..10).collect();
+    let seg_tree: SegTree = SegTree::from_slice(&data);
+    assert_eq!(seg_tree.max_right(0, |&sum| sum <= 10), 5);
+    assert_eq!(seg_tree.max_right(0, |&sum| sum <= 100), 10);
+    assert_eq!(seg_tree.max_right(10, |&sum| sum <= 0), 10);
+    for l in 0..=10
+    {
+        for limit in 0..50
+        {
+            let expected = (l..=10)
+                .rev()
+                .find(|&r| data[l..r].iter().sum::<i32>() <= limit)
+                .unwrap();
+            assert_eq!(seg_tree.max_right(l, |&sum| sum <= limit), expected);
+        }
+    }
+}
+
+#[test]
+fn test_min_left()
+{
+    let data = [3, 0, 4, 1, 5, 9, 2, 6, 5, 3, 5];
+    let seg_tree: SegTree = SegTree::from_slice(&data);
+    assert_eq!(seg_tree.min_left(0, |&sum| sum <= 0), 0);
+    assert_eq!(seg_tree.min_left(11, |&sum| sum < 0), 11);
+    for r in 0..=data.len()
+    {
+        for limit in 0..50
+        {
+            let expected = (0..=r)
+                .find(|&l| data[l..r].iter().sum::<i32>() <= limit)
+                .unwrap();
+            assert_eq!(seg_tree.min_left(r, |&sum| sum <= limit), expected);
+        }
+    }
+}
+
+#[test]
+fn test_range_add_min()
+{
+    let mut seg_tree = LazySegTree::<AddMin>::from_slice(&[6, 3, 1, 4, 8, 2, 9, 7, 5, 10]);
+    seg_tree.range_add(2, 6, 5);
+    assert_eq!(seg_tree.ask(0, 10), 3);
+    assert_eq!(seg_tree.ask(2, 6), 6);
+    assert_eq!(seg_tree.ask(5, 6), 7);
+    seg_tree.range_add(0, 4, -10);
+    assert_eq!(seg_tree.ask(0, 10), -7);
+    assert_eq!(seg_tree.ask(4, 10), 5);
+}
+
+#[test]
+fn test_range_add_min_at_i32_max()
+{
+    let mut seg_tree = LazySegTree::<AddMin>::from_slice(&[i32::MAX, 7]);
+    seg_tree.range_add(0, 2, -10);
+    assert_eq!(seg_tree.ask(0, 1), i32::MAX - 10);
+    assert_eq!(seg_tree.ask(0, 2), -3);
+}
+
+#[test]
+fn test_range_add_min_from_new()
+{
+    let mut seg_tree = LazySegTree::<AddMin>::new(0, 10);
+    seg_tree.range_add(2, 6, 5);
+    assert_eq!(seg_tree.ask(0, 10), i32::MAX);
+    seg_tree.revise(3, 4);
+    seg_tree.range_add(0, 10, -1);
+    assert_eq!(seg_tree.ask(2, 6), 3);
+    assert_eq!(seg_tree.ask(4, 10), i32::MAX - 1);
+}
+
+#[test]
+fn test_nonzero_left_bound()
+{
+    let mut seg_tree: SegTree = SegTree::new(3, 9);
+    seg_tree.revise(5, 7);
+    assert_eq!(seg_tree.ask(3, 6), 7);
+    seg_tree.revise(3, 1);
+    seg_tree.revise(8, 2);
+    assert_eq!(seg_tree.ask(3, 9), 10);
+    assert_eq!(seg_tree.ask(3, 4), 1);
+    assert_eq!(seg_tree.ask(6, 9), 2);
+    assert_eq!(seg_tree.ask(4, 8), 7);
+    assert_eq!(seg_tree.get(8), 2);
+    assert_eq!(seg_tree.to_vec(), vec![1, 0, 7, 0, 0, 2]);
+    assert_eq!(seg_tree.max_right(3, |&sum| sum < 8), 5);
+    assert_eq!(seg_tree.min_left(9, |&sum| sum < 3), 6);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_nonzero_left_bound_revise_below()
+{
+    let mut seg_tree: SegTree = SegTree::new(3, 9);
+    seg_tree.revise(2, 1);
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range")]
+fn test_nonzero_left_bound_ask_below()
+{
+    let seg_tree: SegTree = SegTree::new(3, 9);
+    let _ = seg_tree.ask(2, 5);
+}
+
+#[test]
+fn test_len()
+{
+    assert_eq!(SegTree::<SumMonoid>::new(0, 10).len(), 10);
+    assert_eq!(SegTree::<SumMonoid>::new(3, 9).len(), 6);
+    assert!(!SegTree::<SumMonoid>::from_slice(&[1]).is_empty());
+}
+
+#[test]
+fn test_point_add()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    for _ in 0..3
+    {
+        seg_tree.point_add(3, 2);
+    }
+    assert_eq!(seg_tree.ask(3, 4), 6);
+    seg_tree.point_add(9, -1);
+    assert_eq!(seg_tree.ask(0, 10), 5);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_invalid_point_add()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.point_add(10, 1);
+}
+
+#[test]
+fn test_from_vec_and_iter()
+{
+    let from_vec: SegTree = vec![1, 2, 3].into();
+    assert_eq!(from_vec.get_range(), (0, 3));
+    assert_eq!(from_vec.ask(0, 3), 6);
+    let collected: SegTree = (0..10).collect();
+    assert_eq!(collected.get_range(), (0, 10));
+    assert_eq!(collected.ask(0, 10), 45);
+}
+
+#[test]
+#[should_panic(expected = "Cannot build a tree from empty data")]
+fn test_collect_empty()
+{
+    let _: SegTree = (0..0).collect();
+}
+
+#[test]
+fn test_iter()
+{
+    let mut seg_tree: SegTree = (0..13).map(|x| x * 3 - 7).collect();
+    seg_tree.revise(6, 100);
+    assert_eq!(seg_tree.iter().count(), seg_tree.len());
+    assert_eq!(
+        seg_tree.iter().sum::<i32>(),
+        seg_tree.ask(0, seg_tree.len())
+    );
+    assert_eq!(seg_tree.iter().collect::<Vec<_>>(), seg_tree.to_vec());
+    let mut visited = Vec::new();
+    for value in &seg_tree
+    {
+        visited.push(value);
+    }
+    assert_eq!(visited, seg_tree.to_vec());
+    let single: SegTree = SegTree::from_slice(&[5]);
+    assert_eq!(single.iter().collect::<Vec<_>>(), vec![5]);
+}
+
+#[test]
+fn test_f64_fractional()
+{
+    let mut seg_tree = SegTree::<SumMonoid<f64>>::new(0, 5);
+    for i in 0..5
+    {
+        seg_tree.revise(i, 0.25 * i as f64 + 0.125);
+    }
+    assert_eq!(seg_tree.ask(0, 5), 3.125);
+    assert_eq!(seg_tree.ask(1, 3), 1.0);
+    seg_tree.point_add(4, -1.5);
+    assert_eq!(seg_tree.ask(3, 5), 0.5);
+}
+
+#[test]
+fn test_mod_sum()
+{
+    const MOD: u64 = 1_000_000_007;
+    let values: Vec<u64> = (0..20).map(|i| u64::MAX - i * 998_244_353).collect();
+    let mut seg_tree = SegTree::<ModSumMonoid<MOD>>::new(0, values.len());
+    for (i, &v) in values.iter().enumerate()
+    {
+        seg_tree.revise(i, ModInt::new(v));
+    }
+    for l in 0..values.len()
+    {
+        for r in l + 1..=values.len()
+        {
+            let expected = values[l..r].iter().map(|&v| v as u128).sum::<u128>() % MOD as u128;
+            let residue = seg_tree.ask(l, r).value();
+            assert!(residue < MOD);
+            assert_eq!(residue as u128, expected);
+        }
+    }
+}
+
+#[test]
+fn test_mod_sum_large_modulus()
+{
+    const MOD: u64 = u64::MAX - 58;
+    let seg_tree: SegTree<ModSumMonoid<MOD>> =
+        vec![ModInt::new(MOD - 1), ModInt::new(MOD - 2)].into();
+    assert_eq!(seg_tree.ask(0, 2).value(), MOD - 3);
+}
+
+#[test]
+fn test_total()
+{
+    let mut seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    assert_eq!(seg_tree.total(), 6);
+    seg_tree.revise(1, -2);
+    assert_eq!(seg_tree.total(), seg_tree.ask(0, 3));
+    assert_eq!(SegTree::new_max(4, 9).total(), i32::MIN);
+}
+
+#[test]
+fn test_index()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    assert_eq!(seg_tree[3], 3);
+    assert_eq!(seg_tree[0], 0);
+    assert_eq!(seg_tree[9], 9);
+    seg_tree.revise(3, 30);
+    assert_eq!(seg_tree[3], 30);
+}
+
+#[test]
+fn test_index_nodes_unshared()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    assert_eq!(seg_tree.max_node_refs(), 1);
+    seg_tree.push(10);
+    seg_tree.swap(2, 7);
+    assert_eq!(seg_tree.max_node_refs(), 1);
+
+    let copy = seg_tree.clone();
+    let mapped = seg_tree.map(|value| value * 2);
+    assert_eq!(copy.max_node_refs(), 1);
+    assert_eq!(mapped.max_node_refs(), 1);
+    assert_eq!(seg_tree.max_node_refs(), 1);
+
+    let (left, right) = seg_tree.split(4);
+    assert_eq!(left.max_node_refs(), 1);
+    assert_eq!(right.max_node_refs(), 1);
+    let joined = SegTree::concat(left, right);
+    assert_eq!(joined.max_node_refs(), 1);
+    assert_eq!(joined[2], 7);
+
+    // a node handed out by a test-only accessor is exactly what `Index`
+    // relies on never happening
+    let left = joined.left_child().unwrap();
+    assert_eq!(joined.max_node_refs(), 2);
+    drop(left);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_invalid_index()
+{
+    let seg_tree: SegTree = (0..10).collect();
+    let _ = seg_tree[10];
+}
+
+#[test]
+fn test_range_multiply()
+{
+    let data: Vec<i32> = (0..10).collect();
+    let mut seg_tree = LazySegTree::<AffineSum>::from_slice(&data);
+    seg_tree.range_multiply(0, 10, 2);
+    assert_eq!(seg_tree.ask(0, 10), 90);
+    let mut brute: Vec<i32> = data.iter().map(|x| x * 2).collect();
+    for &(l, r, factor, delta) in &[
+        (2, 7, 1, 3),
+        (0, 5, 3, 0),
+        (4, 10, 1, -2),
+        (3, 6, -1, 0),
+        (1, 9, 2, 1),
+    ]
+    {
+        if factor != 1
+        {
+            seg_tree.range_multiply(l, r, factor);
+        }
+        if delta != 0
+        {
+            seg_tree.range_add(l, r, delta);
+        }
+        brute[l..r]
+            .iter_mut()
+            .for_each(|x| *x = *x * factor + delta);
+    }
+    for l in 0..10
+    {
+        for r in l + 1..=10
+        {
+            assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>());
+        }
+    }
+}
+
+#[test]
+fn test_range_affine_random()
+{
+    let mut state = 0x9e37_79b9_7f4a_7c15;
+    let n = 23;
+    let mut brute: Vec<i32> = (0..n as i32).map(|x| x - 11).collect();
+    let mut seg_tree = LazySegTree::<AffineSum>::from_slice(&brute);
+    for _ in 0..500
+    {
+        let a = next_rand(&mut state) as usize % n;
+        let b = next_rand(&mut state) as usize % n;
+        let (l, r) = (a.min(b), a.max(b) + 1);
+        if next_rand(&mut state).is_multiple_of(2)
+        {
+            let mul = (next_rand(&mut state) % 3) as i32 - 1;
+            let add = (next_rand(&mut state) % 11) as i32 - 5;
+            seg_tree.range_affine(l, r, mul, add);
+            brute[l..r].iter_mut().for_each(|x| *x = mul * *x + add);
+        }
+        else
+        {
+            assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>());
+        }
+    }
+}
+
+#[test]
+fn test_clear()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    for i in 0..10
+    {
+        seg_tree.revise(i, i as i32 + 1);
+    }
+    seg_tree.clear();
+    assert_eq!(seg_tree.ask(0, seg_tree.len()), 0);
+    assert_eq!(seg_tree.to_vec(), vec![0; 10]);
+    seg_tree.revise(4, 3);
+    assert_eq!(seg_tree.total(), 3);
+    let mut min_tree: SegTree<MinMonoid> = (0..5).collect();
+    min_tree.clear();
+    assert_eq!(min_tree.total(), i32::MAX);
+}
+
+#[test]
+fn test_lazy_clear()
+{
+    let mut seg_tree = LazySegTree::<AddSum>::from_slice(&[1, 2, 3, 4, 5]);
+    seg_tree.range_add(1, 4, 10);
+    seg_tree.clear();
+    assert_eq!(seg_tree.ask(0, 5), 0);
+    assert_eq!(seg_tree.ask(2, 3), 0);
+    seg_tree.range_add(0, 2, 1);
+    assert_eq!(seg_tree.ask(0, 5), 2);
+}
+
+#[test]
+fn test_fill()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    seg_tree.fill(3);
+    assert_eq!(seg_tree.total(), 30);
+    assert_eq!(seg_tree.ask(2, 7), 15);
+    assert_eq!(seg_tree.to_vec(), vec![3; 10]);
+    let mut max_tree = SegTree::new_max(0, 7);
+    max_tree.fill(-2);
+    assert_eq!(max_tree.ask(1, 5), -2);
+}
+
+#[test]
+fn test_concurrent_borrow()
+{
+    let mut seg_tree: SegTree = (0..8).collect();
+    let left = seg_tree.left_child().unwrap();
+    let guard = left.borrow_mut();
+    assert_eq!(seg_tree.try_ask(0, 2), Err(SegError::Concurrent));
+    assert_eq!(seg_tree.try_ask(4, 8), Ok(22));
+    assert_eq!(seg_tree.try_revise(1, 10), Err(SegError::Concurrent));
+    assert_eq!(seg_tree.try_revise(6, 10), Err(SegError::Concurrent));
+    drop(guard);
+    assert_eq!(seg_tree.ask(0, 8), 28);
+    assert_eq!(seg_tree.try_revise(6, 10), Ok(()));
+    assert_eq!(seg_tree.ask(0, 8), 32);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_concurrent_readers_and_writer()
+{
+    const LEN: usize = 64;
+    const ROUNDS: i32 = 200;
+    let seg_tree: ConcurrentSegTree = ConcurrentSegTree::new(0, LEN);
+    let writer = {
+        let seg_tree = seg_tree.clone();
+        std::thread::spawn(move || {
+            for round in 0..ROUNDS
+            {
+                for pos in 0..LEN
+                {
+                    seg_tree.revise(pos, round + 1);
+                }
+            }
+        })
+    };
+    let readers: Vec<_> = (0..8)
+        .map(|_| {
+            let seg_tree = seg_tree.clone();
+            std::thread::spawn(move || {
+                // leaves only ever grow, so the total must never go back
+                let mut last = 0;
+                for _ in 0..1000
+                {
+                    let total = seg_tree.ask(0, LEN);
+                    assert!(total >= last);
+                    assert!(total <= ROUNDS * LEN as i32);
+                    last = total;
+                }
+            })
+        })
+        .collect();
+    writer.join().unwrap();
+    for reader in readers
+    {
+        reader.join().unwrap();
+    }
+    assert_eq!(seg_tree.ask(0, LEN), ROUNDS * LEN as i32);
+    assert_eq!(seg_tree.ask(10, 20), ROUNDS * 10);
+    assert_eq!(
+        seg_tree.try_revise(LEN, 0),
+        Err(SegError::OutOfRange { pos: LEN })
+    );
+}
+
+#[test]
+fn test_new_with()
+{
+    let seg_tree: SegTree = SegTree::new_with(0, 5, 2);
+    assert_eq!(seg_tree.total(), 10);
+    assert_eq!(seg_tree.ask(1, 3), 4);
+    assert_eq!(seg_tree.to_vec(), vec![2; 5]);
+    let seg_tree = SegTree::<MinMonoid>::new_with(3, 9, 7);
+    assert_eq!(seg_tree.ask(3, 9), 7);
+    assert_eq!(seg_tree.get_range(), (3, 9));
+}
+
+#[test]
+#[should_panic(expected = "Invalid range: left bound must not exceed right bound")]
+fn test_new_with_reversed()
+{
+    SegTree::<SumMonoid>::new_with(9, 3, 1);
+}
+
+#[test]
+fn test_ask_avg()
+{
+    let seg_tree: SegTree = (0..10).collect();
+    assert_eq!(seg_tree.ask_avg(0, 10), 4.5);
+    assert_eq!(seg_tree.ask_avg(3, 4), 3.0);
+    assert_eq!(seg_tree.ask_avg(2, 6), 3.5);
+    let seg_tree: SegTree<SumMonoid<i64>> = SegTree::from_slice(&[-4, 1]);
+    assert_eq!(seg_tree.ask_avg(0, 2), -1.5);
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range: [3, 3)")]
+fn test_ask_avg_empty()
+{
+    let seg_tree: SegTree = (0..10).collect();
+    let _ = seg_tree.ask_avg(3, 3);
+}
+
+#[test]
+fn test_ask_variance()
+{
+    let values: Vec<i64> = (0..10).collect();
+    let mut seg_tree = SegTree::from_values(&values);
+    assert_eq!(seg_tree.ask(0, 10), (45, 285));
+    assert_eq!(seg_tree.ask_variance(0, 10), 8.25);
+    assert_eq!(seg_tree.ask_variance(4, 5), 0.0);
+    assert_eq!(seg_tree.ask_variance(0, 2), 0.25);
+    seg_tree.revise_value(1, -1);
+    assert_eq!(seg_tree.ask(0, 2), (-1, 1));
+    assert_eq!(seg_tree.ask_variance(0, 2), 0.25);
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range: [0, 0)")]
+fn test_ask_variance_empty()
+{
+    let seg_tree = SegTree::from_values(&[1, 2, 3]);
+    let _ = seg_tree.ask_variance(0, 0);
+}
+
+#[test]
+fn test_max_subarray()
+{
+    let values = [-2, 1, -3, 4, -1, 2, 1, -5, 4];
+    let mut seg_tree = SegTree::new_max_subarray(&values);
+    assert_eq!(seg_tree.ask_max_subarray(0, 9), 6);
+    assert_eq!(seg_tree.ask_max_subarray(0, 3), 1);
+    assert_eq!(seg_tree.ask_max_subarray(6, 8), 1);
+    assert_eq!(seg_tree.ask_max_subarray(7, 8), -5);
+    seg_tree.revise_value(7, 10);
+    assert_eq!(seg_tree.ask_max_subarray(0, 9), 20);
+    // compare every range against a brute force scan
+    let mut state = 7;
+    let values: Vec<i32> = (0..20)
+        .map(|_| (next_rand(&mut state) % 21) as i32 - 10)
+        .collect();
+    let seg_tree = SegTree::new_max_subarray(&values);
+    for l in 0..values.len()
+    {
+        for r in l + 1..=values.len()
+        {
+            let mut best = i32::MIN;
+            for i in l..r
+            {
+                for j in i + 1..=r
+                {
+                    best = best.max(values[i..j].iter().sum());
+                }
+            }
+            assert_eq!(seg_tree.ask_max_subarray(l, r), best);
+        }
+    }
+}
+
+#[test]
+fn test_swap()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    seg_tree.swap(0, 9);
+    assert_eq!(seg_tree.get(0), 9);
+    assert_eq!(seg_tree.get(9), 0);
+    assert_eq!(seg_tree.total(), 45);
+    seg_tree.swap(5, 4);
+    seg_tree.swap(7, 7);
+    assert_eq!(seg_tree.to_vec(), vec![9, 1, 2, 3, 5, 4, 6, 7, 8, 0]);
+    assert_eq!(seg_tree.ask(0, 5), 20);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_swap_out_of_range()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    seg_tree.swap(3, 10);
+}
+
+#[test]
+fn test_apply()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.revise(4, 5);
+    seg_tree.apply(4, |x| x + 100);
+    assert_eq!(seg_tree.get(4), 105);
+    assert_eq!(seg_tree.total(), 105);
+    seg_tree.apply(0, |x| x - 5);
+    assert_eq!(seg_tree.ask(0, 5), 100);
+}
+
+#[test]
+fn test_gcd_monoid()
+{
+    let mut seg_tree = SegTree::<GcdMonoid>::from_slice(&[12, 18, 24]);
+    assert_eq!(seg_tree.ask(0, 3), 6);
+    assert_eq!(seg_tree.ask(0, 2), 6);
+    assert_eq!(seg_tree.ask(2, 3), 24);
+    seg_tree.revise(1, 8);
+    assert_eq!(seg_tree.ask(0, 3), 4);
+    let mut seg_tree = SegTree::new_gcd(0, 4);
+    assert_eq!(seg_tree.ask(0, 4), 0);
+    seg_tree.revise(3, 35);
+    seg_tree.revise(0, 21);
+    assert_eq!(seg_tree.ask(0, 4), 7);
+}
+
+#[test]
+fn test_bitwise_monoids()
+{
+    let mut seg_tree = SegTree::new_xor(0, 3);
+    for (i, value) in [1, 2, 3].into_iter().enumerate()
+    {
+        seg_tree.revise(i, value);
+    }
+    assert_eq!(seg_tree.ask(0, 3), 0);
+    assert_eq!(seg_tree.ask(1, 3), 1);
+    let mut seg_tree = SegTree::new_or(0, 4);
+    seg_tree.revise(0, 0b0001);
+    seg_tree.revise(2, 0b0100);
+    assert_eq!(seg_tree.ask(0, 4), 0b0101);
+    assert_eq!(seg_tree.ask(1, 2), 0);
+    let seg_tree = SegTree::<AndMonoid>::from_slice(&[0b1110, 0b0111, 0b0110]);
+    assert_eq!(seg_tree.ask(0, 2), 0b0110);
+    assert_eq!(seg_tree.ask(0, 3), 0b0110);
+    assert_eq!(SegTree::new_and(0, 2).ask(0, 2), !0);
+}
+
+#[test]
+fn test_max_index()
+{
+    let mut seg_tree = SegTree::new_max_index(&[3, 1, 4, 1, 5]);
+    assert_eq!(seg_tree.ask(0, 5), (5, 4));
+    assert_eq!(seg_tree.ask(0, 3), (4, 2));
+    assert_eq!(seg_tree.ask(1, 2), (1, 1));
+    seg_tree.revise_value(0, 5);
+    assert_eq!(seg_tree.ask(0, 5), (5, 0));
+    assert_eq!(seg_tree.ask(1, 5), (5, 4));
+    seg_tree.revise_value(3, 4);
+    assert_eq!(seg_tree.ask(1, 4), (4, 2));
+}
+
+#[test]
+fn test_merge_sort_tree()
+{
+    let tree = MergeSortTree::from_slice(&[5, 1, 4, 2, 3]);
+    assert_eq!(tree.count_leq(0, 5, 3), 3);
+    assert_eq!(tree.count_leq(0, 5, 0), 0);
+    assert_eq!(tree.count_leq(1, 3, 4), 2);
+    assert_eq!(tree.count_leq(0, 1, 5), 1);
+    let mut state = 11;
+    let data: Vec<i32> = (0..50)
+        .map(|_| (next_rand(&mut state) % 20) as i32 - 10)
+        .collect();
+    let tree = MergeSortTree::from_slice(&data);
+    for _ in 0..500
+    {
+        let l = (next_rand(&mut state) % 50) as usize;
+        let r = l + 1 + (next_rand(&mut state) % (50 - l as u64)) as usize;
+        let x = (next_rand(&mut state) % 24) as i32 - 12;
+        let expected = data[l..r].iter().filter(|&&value| value <= x).count();
+        assert_eq!(tree.count_leq(l, r, x), expected);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range")]
+fn test_merge_sort_tree_invalid_range()
+{
+    let tree = MergeSortTree::from_slice(&[1, 2, 3]);
+    tree.count_leq(2, 4, 0);
+}
+
+#[test]
+fn test_sparse_seg_tree()
+{
+    let mut seg_tree: SparseSegTree = SparseSegTree::new(0, 1 << 30);
+    assert_eq!(seg_tree.node_count(), 1);
+    let positions = [0, 12345, 1 << 20, (1 << 29) + 7, (1 << 30) - 1];
+    for (i, &pos) in positions.iter().enumerate()
+    {
+        seg_tree.revise(pos, i as i32 + 1);
+    }
+    // one path of at most 31 nodes per revised index
+    assert!(seg_tree.node_count() <= 1 + 30 * positions.len());
+    assert_eq!(seg_tree.get_val(), 15);
+    assert_eq!(seg_tree.ask(0, 1 << 30), 15);
+    assert_eq!(seg_tree.ask(1, 1 << 30), 14);
+    assert_eq!(seg_tree.ask(12345, (1 << 20) + 1), 5);
+    assert_eq!(seg_tree.ask(12346, 1 << 20), 0);
+    assert_eq!(seg_tree.ask((1 << 29) + 7, (1 << 29) + 8), 4);
+    seg_tree.revise(12345, 0);
+    assert_eq!(seg_tree.ask(0, 1 << 29), 4);
+    assert_eq!(
+        seg_tree.try_revise(1 << 30, 1),
+        Err(SegError::OutOfRange { pos: 1 << 30 })
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_compressed()
+{
+    let (mut seg_tree, index): (SegTree, _) = SegTree::compressed(&[1000, 5, 1000, 77]);
+    assert_eq!(index.len(), 3);
+    assert_eq!(seg_tree.len(), 3);
+    assert_eq!((index[&5], index[&77], index[&1000]), (0, 1, 2));
+    seg_tree.revise(index[&1000], 4);
+    seg_tree.point_add(index[&5], 1);
+    assert_eq!(seg_tree.ask(index[&5], index[&77] + 1), 1);
+    assert_eq!(seg_tree.total(), 5);
+}
+
+#[test]
+fn test_partial_eq()
+{
+    let data = [4, 8, 15, 16, 23, 42];
+    let from_slice: SegTree = SegTree::from_slice(&data);
+    let mut revised: SegTree = SegTree::new(0, data.len());
+    for (i, &value) in data.iter().enumerate()
+    {
+        revised.revise(i, value);
+    }
+    assert_eq!(from_slice, revised);
+    revised.revise(2, 0);
+    assert_ne!(from_slice, revised);
+    // same leaves over a shifted range are different trees
+    let shifted: SegTree = SegTree::new(1, 7);
+    assert_ne!(SegTree::<SumMonoid>::new(0, 6), shifted);
+}
+
+#[test]
+fn test_height_and_node_count()
+{
+    let seg_tree: SegTree = SegTree::new(0, 8);
+    assert_eq!(seg_tree.height(), 3);
+    assert_eq!(seg_tree.node_count(), 15);
+    let seg_tree: SegTree = SegTree::new(0, 1);
+    assert_eq!((seg_tree.height(), seg_tree.node_count()), (0, 1));
+    // every tree over n leaves has 2n - 1 nodes
+    let seg_tree: SegTree = SegTree::new(3, 13);
+    assert_eq!(seg_tree.height(), 4);
+    assert_eq!(seg_tree.node_count(), 19);
+}
+
+#[test]
+fn test_walk()
+{
+    let seg_tree: SegTree = (0..8).collect();
+    let mut visited = Vec::new();
+    seg_tree.walk(&mut |range, &val, depth| visited.push((range, val, depth)));
+    assert_eq!(visited.len(), 15);
+    assert_eq!(visited[0], ((0, 8), 28, 0));
+    assert_eq!(visited[1], ((0, 4), 6, 1));
+    assert_eq!(visited[2], ((0, 2), 1, 2));
+    assert_eq!(visited[3], ((0, 1), 0, 3));
+    // internal nodes holding more than 5
+    let mut count = 0;
+    seg_tree.walk(&mut |range, &val, _| {
+        if range.1 - range.0 > 1 && val > 5
+        {
+            count += 1;
+        }
+    });
+    assert_eq!(count, 5);
+}
+
+#[test]
+fn test_revise_many()
+{
+    let mut state = 5;
+    let updates: Vec<(usize, i32)> = (0..2000)
+        .map(|_| {
+            let pos = (next_rand(&mut state) % 300) as usize;
+            (pos, (next_rand(&mut state) % 100) as i32)
+        })
+        .collect();
+    let mut batched: SegTree = SegTree::new(0, 300);
+    let mut sequential: SegTree = SegTree::new(0, 300);
+    batched.revise_many(&updates);
+    for &(pos, value) in &updates
+    {
+        sequential.revise(pos, value);
+    }
+    assert_eq!(batched, sequential);
+    assert_eq!(batched.ask(17, 250), sequential.ask(17, 250));
+    batched.revise_many(&[]);
+    assert_eq!(batched, sequential);
+}
+
+#[test]
+#[should_panic(expected = "Target index out of range")]
+fn test_revise_many_out_of_range()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.revise_many(&[(3, 1), (10, 1)]);
+}
+
+#[test]
+fn test_to_dot()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    let expected = "digraph SegTree {
+    node [shape=box, style=filled];
+    n0 [label=\"[0, 3)\\n6\", fillcolor=lightgray];
+    n1 [label=\"[0, 1)\\n1\", fillcolor=lightblue];
+    n0 -> n1;
+    n2 [label=\"[1, 3)\\n5\", fillcolor=lightgray];
+    n3 [label=\"[1, 2)\\n2\", fillcolor=lightblue];
+    n2 -> n3;
+    n4 [label=\"[2, 3)\\n3\", fillcolor=lightblue];
+    n2 -> n4;
+    n0 -> n2;
+}
+";
+    assert_eq!(seg_tree.to_dot(), expected);
+    let seg_tree: SegTree = SegTree::new(0, 8);
+    assert_eq!(seg_tree.to_dot().matches("->").count(), 14);
+}
+
+#[test]
+fn test_second_max()
+{
+    let mut seg_tree = SegTree::new_second_max(&[5, 3, 8, 8, 1]);
+    assert_eq!(seg_tree.ask(0, 5), (8, 8));
+    assert_eq!(seg_tree.ask(0, 3), (8, 5));
+    assert_eq!(seg_tree.ask(0, 2), (5, 3));
+    assert_eq!(seg_tree.ask(4, 5), (1, i32::MIN));
+    seg_tree.revise_value(3, 0);
+    assert_eq!(seg_tree.ask(0, 5), (8, 5));
+    seg_tree.revise_value(4, 9);
+    assert_eq!(seg_tree.ask(1, 5), (9, 8));
+}
+
+#[test]
+fn test_assign_add_random()
+{
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    let n = 31;
+    let mut brute = vec![0; n];
+    let mut seg_tree = LazySegTree::<AssignAddSum>::new(0, n);
+    for _ in 0..2000
+    {
+        let a = next_rand(&mut state) as usize % n;
+        let b = next_rand(&mut state) as usize % n;
+        let (l, r) = (a.min(b), a.max(b) + 1);
+        match next_rand(&mut state) % 3
+        {
+            0 =>
+            {
+                let value = (next_rand(&mut state) % 21) as i32 - 10;
+                seg_tree.range_assign(l, r, value);
+                brute[l..r].iter_mut().for_each(|x| *x = value);
+            }
+            1 =>
+            {
+                let delta = (next_rand(&mut state) % 21) as i32 - 10;
+                seg_tree.range_add(l, r, delta);
+                brute[l..r].iter_mut().for_each(|x| *x += delta);
+            }
+            _ => assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>()),
+        }
+    }
+}
+
+#[test]
+fn test_lower_bound()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, 0, 2, 0, 1]);
+    let picks: Vec<usize> = (0..4).map(|t| seg_tree.lower_bound(t)).collect();
+    assert_eq!(picks, vec![0, 2, 2, 4]);
+    assert_eq!(seg_tree.lower_bound(4), 5);
+    // every draw below the total lands on an index with positive weight
+    let seg_tree: SegTree<SumMonoid<u64>> = SegTree::from_slice(&[0, 3, 0, 0, 5, 1]);
+    let mut counts = [0; 6];
+    for t in 0..seg_tree.total()
+    {
+        counts[seg_tree.lower_bound(t)] += 1;
+    }
+    assert_eq!(counts, [0, 3, 0, 0, 5, 1]);
+}
+
+#[test]
+fn test_kth_one()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    for pos in [2, 5, 7]
+    {
+        seg_tree.revise(pos, 1);
+    }
+    assert_eq!(seg_tree.kth_one(1), 2);
+    assert_eq!(seg_tree.kth_one(2), 5);
+    assert_eq!(seg_tree.kth_one(3), 7);
+    seg_tree.revise(5, 0);
+    seg_tree.revise(9, 1);
+    assert_eq!(seg_tree.kth_one(2), 7);
+    assert_eq!(seg_tree.kth_one(3), 9);
+}
+
+#[test]
+#[should_panic(expected = "Fewer than k ones")]
+fn test_kth_one_missing()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[0, 1, 0]);
+    seg_tree.kth_one(2);
+}
+
+#[test]
+#[should_panic(expected = "Negative total")]
+fn test_kth_one_negative_total()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, -3, 1]);
+    seg_tree.kth_one(1);
+}
+
+#[test]
+fn test_extend()
+{
+    let pairs = [(0, 5), (3, 7), (9, 1), (3, 2)];
+    let mut extended: SegTree = SegTree::new(0, 10);
+    extended.extend(pairs);
+    let mut revised: SegTree = SegTree::new(0, 10);
+    for (pos, value) in pairs
+    {
+        revised.revise(pos, value);
+    }
+    assert_eq!(extended, revised);
+    assert_eq!(extended.total(), 8);
+    extended.extend((0..10).map(|i| (i, 1)));
+    assert_eq!(extended.total(), 10);
+}
+
+#[test]
+fn test_split()
+{
+    let data: Vec<i32> = (0..10).map(|x| x * x - 7).collect();
+    for mid in 1..10
+    {
+        let seg_tree: SegTree = SegTree::from_slice(&data);
+        let (left, right) = seg_tree.split(mid);
+        assert_eq!(left.get_range(), (0, mid));
+        assert_eq!(right.get_range(), (mid, 10));
+        // same shape and contents as trees built directly over each half
+        assert_eq!(left.to_vec(), data[..mid]);
+        assert_eq!(right.to_vec(), data[mid..]);
+        assert_eq!(left.node_count(), 2 * mid - 1);
+        assert_eq!(right.node_count(), 2 * (10 - mid) - 1);
+        for l in 0..mid
+        {
+            for r in l + 1..=mid
+            {
+                assert_eq!(left.ask(l, r), data[l..r].iter().sum::<i32>());
+            }
+        }
+        for l in mid..10
+        {
+            assert_eq!(right.ask(l, 10), data[l..].iter().sum::<i32>());
+        }
+    }
+    let (mut left, right) = SegTree::<SumMonoid>::from_slice(&data).split(4);
+    left.revise(0, 100);
+    assert_eq!(left.total(), 100 + data[1..4].iter().sum::<i32>());
+    assert_eq!(right.total(), data[4..].iter().sum::<i32>());
+}
+
+#[test]
+#[should_panic(expected = "Invalid split position")]
+fn test_split_at_bound()
+{
+    let seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.split(10);
+}
+
+#[test]
+fn test_concat()
+{
+    let mut left: SegTree = SegTree::new(0, 4);
+    let mut right: SegTree = SegTree::new(4, 10);
+    left.revise(1, 3);
+    right.revise(4, 5);
+    right.revise(9, 2);
+    let mut seg_tree = SegTree::concat(left, right);
+    assert_eq!(seg_tree.get_range(), (0, 10));
+    assert_eq!(seg_tree.to_vec(), vec![0, 3, 0, 0, 5, 0, 0, 0, 0, 2]);
+    assert_eq!(seg_tree.ask(1, 5), 8);
+    assert_eq!(seg_tree[9], 2);
+    seg_tree.revise(3, 1);
+    assert_eq!(seg_tree.total(), 11);
+    // splitting undoes the concatenation
+    let (left, right) = seg_tree.split(4);
+    assert_eq!(left.to_vec(), vec![0, 3, 0, 1]);
+    assert_eq!(right.total(), 7);
+}
+
+#[test]
+#[should_panic(expected = "not adjacent")]
+fn test_concat_gap()
+{
+    let left: SegTree = SegTree::new(0, 4);
+    let right: SegTree = SegTree::new(5, 10);
+    SegTree::concat(left, right);
+}
+
+#[test]
+fn test_threshold_seg_tree()
+{
+    let mut state = 3;
+    let n = 40;
+    let mut brute = vec![0; n];
+    let mut seg_tree = ThresholdSegTree::new(0, n, 0);
+    assert_eq!(seg_tree.count_above(0, n), 0);
+    for _ in 0..1000
+    {
+        let pos = (next_rand(&mut state) % n as u64) as usize;
+        let value = (next_rand(&mut state) % 11) as i32 - 5;
+        seg_tree.revise(pos, value);
+        brute[pos] = value;
+        let l = (next_rand(&mut state) % n as u64) as usize;
+        let r = l + 1 + (next_rand(&mut state) % (n - l) as u64) as usize;
+        let expected = brute[l..r].iter().filter(|&&value| value > 0).count();
+        assert_eq!(seg_tree.count_above(l, r), expected);
+    }
+    let seg_tree = ThresholdSegTree::from_slice(&[5, 6, 7], 6);
+    assert_eq!(seg_tree.threshold(), 6);
+    assert_eq!(seg_tree.count_above(0, 3), 1);
+}
+
+#[test]
+fn test_diff()
+{
+    let seg_tree: SegTree = SegTree::new_with(5, 15, 1);
+    let mut changed = seg_tree.clone();
+    changed.revise(6, 4);
+    changed.revise(12, -2);
+    assert_eq!(seg_tree.diff(&changed), vec![6, 12]);
+    assert_eq!(changed.diff(&seg_tree), vec![6, 12]);
+    assert!(seg_tree.diff(&seg_tree.clone()).is_empty());
+    // equal sums over a subtree must not hide differing leaves
+    let a: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    let b: SegTree = SegTree::from_slice(&[2, 1, 3, 4]);
+    assert_eq!(a.diff(&b), vec![0, 1]);
+}
+
+#[test]
+#[should_panic(expected = "different ranges")]
+fn test_diff_mismatched_ranges()
+{
+    let a: SegTree = SegTree::new(0, 4);
+    let b: SegTree = SegTree::new(0, 5);
+    a.diff(&b);
+}
+
+#[test]
+fn test_overflow_modes()
+{
+    let mut saturating = SegTree::new_saturating(0, 4);
+    let mut wrapping = SegTree::new_wrapping(0, 4);
+    let mut checked = SegTree::new_checked(0, 4);
+    for pos in 0..3
+    {
+        saturating.revise(pos, i32::MAX - 1);
+        wrapping.revise(pos, i32::MAX - 1);
+        checked.revise_value(pos, i32::MAX - 1);
+    }
+    assert_eq!(saturating.ask(0, 4), i32::MAX);
+    assert_eq!(saturating.ask(1, 2), i32::MAX - 1);
+    assert_eq!(wrapping.ask(0, 3), (i32::MAX - 1).wrapping_mul(3));
+    assert_eq!(checked.ask_checked(2, 4), Ok(i32::MAX - 1));
+    assert_eq!(
+        checked.ask_checked(0, 2),
+        Err(SegError::Overflow { l: 0, r: 2 })
+    );
+    assert_eq!(
+        checked.ask_checked(0, 5),
+        Err(SegError::InvalidRange { l: 0, r: 5 })
+    );
+    // once the offending leaf goes away the sum is valid again
+    checked.revise_value(1, -5);
+    assert_eq!(checked.ask_checked(0, 2), Ok(i32::MAX - 6));
+}
+
+#[test]
+fn test_shift()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    let before = seg_tree.ask(0, 10);
+    seg_tree.shift(5);
+    assert_eq!(seg_tree.get_range(), (5, 15));
+    assert_eq!(seg_tree.ask(5, 15), before);
+    assert_eq!(seg_tree.get(7), 2);
+    seg_tree.revise(14, 100);
+    assert_eq!(seg_tree.ask(10, 15), 5 + 6 + 7 + 8 + 100);
+    seg_tree.shift(-5);
+    assert_eq!(seg_tree.to_vec(), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 100]);
+    assert_eq!(
+        seg_tree.try_ask(0, 11),
+        Err(SegError::InvalidRange { l: 0, r: 11 })
+    );
+}
+
+#[test]
+#[should_panic(expected = "Shifted range out of bounds")]
+fn test_shift_below_zero()
+{
+    let mut seg_tree: SegTree = SegTree::new(3, 10);
+    seg_tree.shift(-4);
+}
+
+#[test]
+fn test_seg_tree_2d()
+{
+    let (rows, cols) = (4, 4);
+    let mut grid: SegTree2D = SegTree2D::new(rows, cols);
+    let mut brute = [[0; 4]; 4];
+    let mut state = 17;
+    for _ in 0..30
+    {
+        let (r, c) = (
+            (next_rand(&mut state) % 4) as usize,
+            (next_rand(&mut state) % 4) as usize,
+        );
+        let value = (next_rand(&mut state) % 19) as i32 - 9;
+        grid.revise(r, c, value);
+        brute[r][c] = value;
+        for r1 in 0..rows
+        {
+            for r2 in r1 + 1..=rows
+            {
+                for c1 in 0..cols
+                {
+                    for c2 in c1 + 1..=cols
+                    {
+                        let expected: i32 = brute[r1..r2]
+                            .iter()
+                            .map(|row| row[c1..c2].iter().sum::<i32>())
+                            .sum();
+                        assert_eq!(grid.ask(r1, c1, r2, c2), expected);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(SegTree2D::<MinMonoid>::new(3, 5).dims(), (3, 5));
+}
+
+#[test]
+fn test_matrix_product_order()
+{
+    let mut state = 23;
+    let n = 12;
+    let mut mats: Vec<Matrix2> = (0..n)
+        .map(|_| {
+            let mut m = [[0; 2]; 2];
+            m.iter_mut()
+                .flatten()
+                .for_each(|cell| *cell = (next_rand(&mut state) % 3) as i64);
+            m
+        })
+        .collect();
+    let product = |mats: &[Matrix2]| {
+        mats.iter().fold(Matrix2Monoid::identity(), |acc, m| {
+            Matrix2Monoid::combine(&acc, m)
+        })
+    };
+    let mut seg_tree = SegTree::<Matrix2Monoid>::from_slice(&mats);
+    let array_tree = ArraySegTree::<Matrix2Monoid>::from_slice(&mats);
+    let persistent_tree = PersistentSegTree::<Matrix2Monoid>::from_slice(&mats);
+    for l in 0..n
+    {
+        for r in l + 1..=n
+        {
+            assert_eq!(seg_tree.ask(l, r), product(&mats[l..r]));
+            assert_eq!(array_tree.ask(l, r), product(&mats[l..r]));
+            assert_eq!(persistent_tree.ask(l, r), product(&mats[l..r]));
+        }
+    }
+    // the two orders really differ, so the checks above are meaningful
+    let (a, b) = ([[1, 1], [0, 1]], [[1, 0], [1, 1]]);
+    assert_ne!(
+        Matrix2Monoid::combine(&a, &b),
+        Matrix2Monoid::combine(&b, &a)
+    );
+    mats[3] = a;
+    mats[4] = b;
+    seg_tree.revise(3, a);
+    seg_tree.revise(4, b);
+    assert_eq!(seg_tree.ask(2, 6), product(&mats[2..6]));
+    // Fibonacci: the n-th power of [[1, 1], [1, 0]] holds F(n + 1)
+    let fib = SegTree::<Matrix2Monoid>::new_with(0, 10, [[1, 1], [1, 0]]);
+    assert_eq!(fib.total()[0][0], 89);
+}
+
+struct ConcatMonoid;
+
+impl Monoid for ConcatMonoid
+{
+    type Item = String;
+    fn identity() -> String
+    {
+        String::new()
+    }
+    fn combine(a: &String, b: &String) -> String
+    {
+        format!("{}{}", a, b)
+    }
+}
+
+#[test]
+fn test_non_commutative_order()
+{
+    let text = "segmenttrees";
+    let chars: Vec<String> = text.chars().map(String::from).collect();
+    let n = chars.len();
+    let seg_tree = SegTree::<ConcatMonoid>::from_slice(&chars);
+    let array_tree = ArraySegTree::<ConcatMonoid>::from_slice(&chars);
+    let persistent_tree = PersistentSegTree::<ConcatMonoid>::from_slice(&chars);
+    let mut sparse_tree = SparseSegTree::<ConcatMonoid>::new(0, n);
+    for (i, c) in chars.iter().enumerate()
+    {
+        sparse_tree.revise(i, c.clone());
+    }
+    for l in 0..n
+    {
+        for r in l + 1..=n
+        {
+            assert_eq!(seg_tree.ask(l, r), text[l..r]);
+            assert_eq!(array_tree.ask(l, r), text[l..r]);
+            assert_eq!(persistent_tree.ask(l, r), text[l..r]);
+            assert_eq!(sparse_tree.ask(l, r), text[l..r]);
+        }
+    }
+    // the update paths must recombine in order too
+    let mut seg_tree = seg_tree;
+    seg_tree.revise(0, "S".to_string());
+    seg_tree.swap(3, 10);
+    seg_tree.revise_many(&[(11, "!".to_string()), (5, "E".to_string())]);
+    assert_eq!(seg_tree.total(), "SegeeEttrem!");
+    let version = persistent_tree.revise(6, "N".to_string());
+    assert_eq!(version.ask(4, 8), "enNt");
+    assert_eq!(seg_tree.max_right(0, |s| s.len() <= 3), 3);
+    assert_eq!(seg_tree.min_left(n, |s| !s.contains('r')), 9);
+    let (left, right) = seg_tree.split(5);
+    assert_eq!(left.total(), "Segee");
+    assert_eq!(right.total(), "Ettrem!");
+    assert_eq!(SegTree::concat(left, right).ask(3, 8), "eeEtt");
+}
+
+#[test]
+fn test_poly_hash()
+{
+    type Hash = PolyHashMonoid<131, 1_000_000_007>;
+    let text = "abracadabra";
+    let mut seg_tree = SegTree::<Hash>::new_poly_hash(text);
+    assert_eq!(seg_tree.ask_hash(0, 4), seg_tree.ask_hash(7, 11));
+    assert_eq!(seg_tree.ask_hash(0, 1), 'a' as u64);
+    assert_eq!(seg_tree.ask_hash(0, 2), ('a' as u64 * 131 + 'b' as u64));
+    // every pair of substrings hashes equally exactly when they are equal
+    let n = text.len();
+    for (l1, r1, l2, r2) in (0..n).flat_map(|l1| {
+        (l1 + 1..=n)
+            .flat_map(move |r1| (0..=n - (r1 - l1)).map(move |l2| (l1, r1, l2, l2 + r1 - l1)))
+    })
+    {
+        assert_eq!(
+            seg_tree.ask_hash(l1, r1) == seg_tree.ask_hash(l2, r2),
+            text[l1..r1] == text[l2..r2]
+        );
+    }
+    seg_tree.revise_value(10, 'x' as u64);
+    assert_ne!(seg_tree.ask_hash(0, 4), seg_tree.ask_hash(7, 11));
+    assert_eq!(seg_tree.ask_hash(7, 10), seg_tree.ask_hash(0, 3));
+}
+
+#[test]
+fn test_prefix_sums()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    assert_eq!(seg_tree.prefix_sums(), vec![0, 1, 3, 6]);
+    let mut seg_tree: SegTree = SegTree::new(4, 9);
+    seg_tree.revise(6, -2);
+    seg_tree.revise(8, 5);
+    let sums = seg_tree.prefix_sums();
+    assert_eq!(sums.len(), seg_tree.len() + 1);
+    for (i, &sum) in sums.iter().enumerate().skip(1)
+    {
+        assert_eq!(sum, seg_tree.ask(4, 4 + i));
+    }
+}
+
+#[test]
+fn test_undo()
+{
+    let mut seg_tree: UndoSegTree = UndoSegTree::from_slice(&[1, 2, 3, 4]);
+    seg_tree.revise(3, 10);
+    seg_tree.revise(3, 20);
+    seg_tree.revise(0, 5);
+    assert_eq!(seg_tree.ask(0, 4), 30);
+    assert!(seg_tree.undo());
+    assert_eq!(seg_tree.tree().to_vec(), vec![1, 2, 3, 20]);
+    assert!(seg_tree.undo());
+    assert_eq!(seg_tree.tree().get(3), 10);
+    assert!(seg_tree.undo());
+    assert_eq!(seg_tree.ask(0, 4), 10);
+    assert!(!seg_tree.undo());
+    // only the last two revises are kept
+    seg_tree.set_undo_limit(2);
+    for value in 1..=5
+    {
+        seg_tree.revise(1, value);
+    }
+    assert_eq!(seg_tree.undo_len(), 2);
+    assert!(seg_tree.undo());
+    assert!(seg_tree.undo());
+    assert!(!seg_tree.undo());
+    assert_eq!(seg_tree.tree().get(1), 3);
+    seg_tree.set_undo_limit(0);
+    seg_tree.revise(1, 9);
+    assert!(!seg_tree.undo());
+}
+
+#[test]
+fn test_push()
+{
+    let mut seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    seg_tree.push(4);
+    assert_eq!(seg_tree.len(), 4);
+    assert_eq!(seg_tree.get(3), 4);
+    assert_eq!(seg_tree.ask(1, 4), 9);
+    let mut brute = vec![1, 2, 3, 4];
+    for value in 5..=1000
+    {
+        seg_tree.push(value);
+        brute.push(value);
+    }
+    assert_eq!(seg_tree.to_vec(), brute);
+    assert_eq!(seg_tree.node_count(), 2 * brute.len() - 1);
+    assert!(seg_tree.height() <= 2 * 10 + 2);
+    assert_eq!(seg_tree.ask(17, 503), brute[17..503].iter().sum::<i32>());
+    seg_tree.revise(999, 0);
+    assert_eq!(seg_tree[999], 0);
+    assert_eq!(seg_tree.max_right(0, |&sum| sum <= 10), 4);
+    let mut single: SegTree = SegTree::new(7, 8);
+    single.push(5);
+    assert_eq!((single.get_range(), single.total()), ((7, 9), 5));
+}
+
+#[test]
+fn test_memory_bytes()
+{
+    let seg_tree: SegTree = SegTree::new(0, 1000);
+    let node =
+        std::mem::size_of::<std::cell::RefCell<SegTree>>() + 2 * std::mem::size_of::<usize>();
+    assert_eq!(
+        seg_tree.memory_bytes(),
+        std::mem::size_of::<SegTree>() + 1998 * node
+    );
+    // the flat array pads to 1024 leaves but saves the per-node pointers
+    let array_tree: ArraySegTree = ArraySegTree::new(0, 1000);
+    assert!(array_tree.memory_bytes() >= 2048 * std::mem::size_of::<i32>());
+    assert!(array_tree.memory_bytes() < seg_tree.memory_bytes());
+}
+
+#[test]
+fn test_empty_tree()
+{
+    let mut seg_tree: SegTree = SegTree::new(5, 5);
+    assert!(seg_tree.is_empty());
+    assert_eq!(seg_tree.len(), 0);
+    assert_eq!(seg_tree.total(), 0);
+    assert_eq!(seg_tree.ask(5, 5), 0);
+    assert_eq!(seg_tree.to_vec(), Vec::<i32>::new());
+    assert_eq!(
+        seg_tree.try_revise(5, 1),
+        Err(SegError::OutOfRange { pos: 5 })
+    );
+
+    seg_tree.push(7);
+    assert_eq!(seg_tree.get_range(), (5, 6));
+    assert_eq!(seg_tree.total(), 7);
+
+    let full: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    assert_eq!(full.ask(1, 1), 0);
+    assert_eq!(SegTree::concat(full, SegTree::new(3, 3)).node_count(), 5);
+}
+
+#[test]
+fn test_empty_tree_other_kinds()
+{
+    let array_tree: ArraySegTree = ArraySegTree::new(5, 5);
+    assert_eq!(array_tree.ask(5, 5), 0);
+    assert_eq!(
+        array_tree.try_ask(5, 6),
+        Err(SegError::InvalidRange { l: 5, r: 6 })
+    );
+    let fn_tree = FnSegTree::with_combine(5, 5, i32::MAX, |a, b| a.min(b));
+    assert_eq!(fn_tree.ask(5, 5), i32::MAX);
+    let sparse_tree: SparseSegTree = SparseSegTree::new(5, 5);
+    assert_eq!(sparse_tree.ask(5, 5), 0);
+    let persistent_tree: PersistentSegTree = PersistentSegTree::new(5, 5);
+    assert_eq!(persistent_tree.ask(5, 5), 0);
+    let mut lazy_tree = LazySegTree::<AddSum>::new(5, 5);
+    assert_eq!(lazy_tree.ask(5, 5), 0);
+    let mut beats_tree = BeatsSegTree::new(5, 5);
+    assert_eq!(beats_tree.ask(5, 5), 0);
+    assert_eq!(beats_tree.ask_max(5, 5), i32::MIN);
+
+    // an empty subrange of a non-empty tree is the identity as well
+    let mut lazy_tree = LazySegTree::<AddSum>::from_slice(&[1, 2, 3]);
+    assert_eq!(lazy_tree.ask(2, 2), 0);
+    let mut beats_tree = BeatsSegTree::from_slice(&[1, 2, 3]);
+    assert_eq!(beats_tree.ask(1, 1), 0);
+    let persistent_tree: PersistentSegTree = PersistentSegTree::from_slice(&[1, 2, 3]);
+    assert_eq!(persistent_tree.ask(3, 3), 0);
+}
+
+#[test]
+fn test_empty_range_updates()
+{
+    let mut lazy_tree = LazySegTree::<AddSum>::new(5, 5);
+    lazy_tree.range_add(5, 5, 3);
+    assert_eq!(lazy_tree.ask(5, 5), 0);
+    let mut lazy_tree = LazySegTree::<AddSum>::from_slice(&[1, 2, 3]);
+    lazy_tree.apply_range(1, 1, 10);
+    assert_eq!(lazy_tree.ask(0, 3), 6);
+
+    let mut beats_tree = BeatsSegTree::new(5, 5);
+    beats_tree.range_chmin(5, 5, -1);
+    assert_eq!(beats_tree.ask(5, 5), 0);
+    let mut beats_tree = BeatsSegTree::from_slice(&[4, 8, 2]);
+    beats_tree.range_chmin(2, 2, 0);
+    assert_eq!(beats_tree.ask(0, 3), 14);
+
+    let merge_tree = MergeSortTree::from_slice(&[5, 1, 4]);
+    assert_eq!(merge_tree.count_leq(1, 1, 10), 0);
+    assert_eq!(merge_tree.count_leq(3, 3, 10), 0);
+}
+
+#[test]
+#[should_panic(expected = "Invalid range: left bound must not exceed right bound")]
+fn test_persistent_invalid_build()
+{
+    PersistentSegTree::<SumMonoid>::new(10, 0);
+}
+
+#[test]
+fn test_rebuild()
+{
+    let mut seg_tree: SegTree = (0..10).collect();
+    let data: Vec<i32> = (0..10).map(|i| i * i).collect();
+    seg_tree.rebuild(&data);
+    for l in 0..10
+    {
+        for r in l + 1..=10
+        {
+            assert_eq!(seg_tree.ask(l, r), data[l..r].iter().sum::<i32>());
+        }
+    }
+    assert_eq!(seg_tree.node_count(), 19);
+
+    let mut shifted: SegTree = SegTree::new(3, 6);
+    shifted.rebuild(&[1, 2, 3]);
+    assert_eq!(shifted.ask(4, 6), 5);
+}
+
+#[test]
+#[should_panic(expected = "Data length does not match")]
+fn test_rebuild_wrong_length()
+{
+    let mut seg_tree: SegTree = SegTree::new(0, 10);
+    seg_tree.rebuild(&[1, 2, 3]);
+}
+
+#[test]
+fn test_lazy_progression()
+{
+    let mut seg_tree = LazySegTree::<ProgressionSum>::new(0, 37);
+    let mut brute = [0; 37];
+    let mut state = 11;
+    for _ in 0..300
+    {
+        let a = (next_rand(&mut state) % 38) as usize;
+        let b = (next_rand(&mut state) % 38) as usize;
+        let (l, r) = (a.min(b), a.max(b));
+        if l == r
+        {
+            continue;
+        }
+        if next_rand(&mut state).is_multiple_of(2)
+        {
+            let start = (next_rand(&mut state) % 21) as i32 - 10;
+            let step = (next_rand(&mut state) % 7) as i32 - 3;
+            seg_tree.range_add_progression(l, r, start, step);
+            for (k, x) in brute[l..r].iter_mut().enumerate()
+            {
+                *x += start + k as i32 * step;
+            }
+        }
+        else
+        {
+            assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>());
+        }
+    }
+}
+
+#[test]
+fn test_contains_range()
+{
+    let seg_tree: SegTree = SegTree::new(3, 9);
+    assert!(!seg_tree.contains(2));
+    assert!(seg_tree.contains(8));
+    for l in 0..12
+    {
+        for r in 0..12
+        {
+            assert_eq!(
+                seg_tree.contains_range(l, r),
+                seg_tree.try_ask(l, r).is_ok()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_map()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3]);
+    let doubled = seg_tree.map(|x| x * 2);
+    assert_eq!(doubled.total(), 12);
+    assert_eq!(doubled.to_vec(), vec![2, 4, 6]);
+    assert_eq!(doubled.node_count(), seg_tree.node_count());
+    assert_eq!(seg_tree.to_vec(), vec![1, 2, 3]);
+
+    let max_tree = SegTree::new_max(0, 4).map(|_| 7);
+    assert_eq!(max_tree.ask(1, 3), 7);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_from_slice()
+{
+    let mut state = 5;
+    let data: Vec<i32> = (0..100_003)
+        .map(|_| (next_rand(&mut state) % 1000) as i32)
+        .collect();
+    let serial: SegTree = SegTree::from_slice(&data);
+    let parallel: SegTree = SegTree::par_from_slice(&data);
+    assert!(parallel == serial);
+    assert_eq!(parallel.height(), serial.height());
+    assert_eq!(parallel.node_count(), serial.node_count());
+    for _ in 0..100
+    {
+        let a = (next_rand(&mut state) % 100_004) as usize;
+        let b = (next_rand(&mut state) % 100_004) as usize;
+        let (l, r) = (a.min(b), a.max(b));
+        assert_eq!(parallel.ask(l, r), data[l..r].iter().sum::<i32>());
+    }
+}
+
+#[test]
+fn test_find_first()
+{
+    let mut state = 17;
+    let data: Vec<i32> = (0..50)
+        .map(|_| (next_rand(&mut state) % 100) as i32 - 50)
+        .collect();
+    let sum_tree: SegTree = SegTree::from_slice(&data);
+    let mut max_tree = SegTree::new_max(0, 50);
+    for (i, &x) in data.iter().enumerate()
+    {
+        max_tree.revise(i, x);
+    }
+    for l in 0..=50
+    {
+        for t in [-60, -10, 0, 25, 45, 60]
+        {
+            let expected = (l..50).find(|&i| data[i] > t);
+            assert_eq!(sum_tree.find_first(l, |&x| x > t), expected);
+            assert_eq!(max_tree.find_first_pruned(l, |&x| x > t), expected);
+        }
+    }
+}
+
+#[test]
+fn test_beats_chmin()
+{
+    let mut state = 23;
+    let mut brute: Vec<i32> = (0..41)
+        .map(|_| (next_rand(&mut state) % 200) as i32 - 100)
+        .collect();
+    let mut seg_tree = BeatsSegTree::from_slice(&brute);
+    for _ in 0..500
+    {
+        let a = (next_rand(&mut state) % 42) as usize;
+        let b = (next_rand(&mut state) % 42) as usize;
+        let (l, r) = (a.min(b), a.max(b));
+        if l == r
+        {
+            continue;
+        }
+        match next_rand(&mut state) % 4
+        {
+            0 | 1 =>
+            {
+                let x = (next_rand(&mut state) % 200) as i32 - 100;
+                seg_tree.range_chmin(l, r, x);
+                for v in &mut brute[l..r]
+                {
+                    *v = (*v).min(x);
+                }
+            }
+            2 =>
+            {
+                let x = (next_rand(&mut state) % 200) as i32 - 100;
+                seg_tree.revise(l, x);
+                brute[l] = x;
+            }
+            _ =>
+            {
+                assert_eq!(seg_tree.ask(l, r), brute[l..r].iter().sum::<i32>());
+                assert_eq!(seg_tree.ask_max(l, r), *brute[l..r].iter().max().unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_try_from_slice()
+{
+    let empty: Result<SegTree, _> = SegTree::try_from_slice(&[]);
+    match empty
+    {
+        Err(err) =>
+        {
+            assert_eq!(err, SegError::Empty);
+            assert_eq!(err.to_string(), "Cannot build a tree from empty data");
+        }
+        Ok(_) => panic!("expected an error"),
+    }
+    let seg_tree: SegTree = SegTree::try_from_slice(&[1, 2, 3]).unwrap();
+    assert_eq!(seg_tree.ask(1, 3), 5);
+}
+
+#[test]
+fn test_seg_tree_n()
+{
+    let mut inline = SegTreeN::<8>::new();
+    let mut heap: SegTree = SegTree::new(0, 8);
+    let mut state = 29;
+    for _ in 0..200
+    {
+        let pos = (next_rand(&mut state) % 8) as usize;
+        let value = (next_rand(&mut state) % 100) as i32;
+        inline.revise(pos, value);
+        heap.revise(pos, value);
+        for l in 0..=8
+        {
+            for r in l..=8
+            {
+                assert_eq!(inline.ask(l, r), heap.ask(l, r));
+            }
+        }
+    }
+    assert_eq!(
+        inline.try_revise(8, 1),
+        Err(SegError::OutOfRange { pos: 8 })
+    );
+}
+
+#[test]
+fn test_seg_tree_n_non_commutative()
+{
+    let text = "segmenttrees";
+    let mut seg_tree = SegTreeN::<12, ConcatMonoid>::new();
+    for (i, c) in text.chars().enumerate()
+    {
+        seg_tree.revise(i, c.to_string());
+    }
+    for l in 0..=12
+    {
+        for r in l..=12
+        {
+            assert_eq!(seg_tree.ask(l, r), &text[l..r]);
+        }
+    }
+}
+
+#[test]
+fn test_child_accessors()
+{
+    let seg_tree: SegTree = (0..7).collect();
+    let (left, right) = (
+        seg_tree.left_range().unwrap(),
+        seg_tree.right_range().unwrap(),
+    );
+    assert_eq!(left, (0, 3));
+    assert_eq!(left.1, right.0);
+    assert_eq!(seg_tree.left_val(), Some(seg_tree.ask(0, 3)));
+    assert_eq!(seg_tree.right_val(), Some(seg_tree.ask(3, 7)));
+
+    // `concat` splits at the boundary rather than at the midpoint
+    let joined = SegTree::concat(SegTree::<SumMonoid>::new(0, 1), SegTree::new(1, 8));
+    assert_eq!(joined.left_range(), Some((0, 1)));
+
+    let leaf: SegTree = SegTree::new(0, 1);
+    assert_eq!(leaf.left_range(), None);
+    assert_eq!(leaf.right_val(), None);
+}
+
+#[test]
+fn test_ask_inclusive()
+{
+    let seg_tree: SegTree = (0..10).collect();
+    assert_eq!(seg_tree.ask_inclusive(3, 6), seg_tree.ask(3, 7));
+    assert_eq!(seg_tree.ask_inclusive(4, 4), 4);
+    assert_eq!(seg_tree.ask_inclusive(0, 9), 45);
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range: [5, 10]")]
+fn test_ask_inclusive_past_end()
+{
+    let seg_tree: SegTree = SegTree::new(0, 10);
+    let _ = seg_tree.ask_inclusive(5, 10);
+}
+
+std::thread_local! {
+    static COMBINES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+// sums `i32`s while counting the calls to `combine` made on this thread
+struct CountingMonoid;
+
+impl Monoid for CountingMonoid
+{
+    type Item = i32;
+    fn identity() -> i32
+    {
+        0
+    }
+    fn combine(a: &i32, b: &i32) -> i32
+    {
+        COMBINES.with(|count| count.set(count.get() + 1));
+        a + b
+    }
+}
+
+#[test]
+fn test_revise_checked()
+{
+    let mut seg_tree = SegTree::<CountingMonoid>::new(0, 10);
+    assert!(seg_tree.revise_checked(3, 7));
+    let before = COMBINES.with(|count| count.get());
+    assert!(!seg_tree.revise_checked(3, 7));
+    assert_eq!(COMBINES.with(|count| count.get()), before);
+    assert!(seg_tree.revise_checked(3, 8));
+    assert!(COMBINES.with(|count| count.get()) > before);
+    assert_eq!(seg_tree.ask(0, 10), 8);
+}
+
+#[test]
+fn test_offline_distinct()
+{
+    let mut state = 31;
+    let data: Vec<i32> = (0..60)
+        .map(|_| (next_rand(&mut state) % 8) as i32)
+        .collect();
+    let queries: Vec<(usize, usize)> = (0..200)
+        .map(|_| {
+            let a = (next_rand(&mut state) % 61) as usize;
+            let b = (next_rand(&mut state) % 61) as usize;
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    let expected: Vec<usize> = queries
+        .iter()
+        .map(|&(l, r)| {
+            let mut seen: Vec<i32> = data[l..r].to_vec();
+            seen.sort_unstable();
+            seen.dedup();
+            seen.len()
+        })
+        .collect();
+    assert_eq!(offline_distinct(&data, &queries), expected);
+    assert_eq!(offline_distinct(&[], &[(0, 0)]), vec![0]);
+}
+
+#[test]
+fn test_merge_with()
+{
+    let mut seg_tree: SegTree = SegTree::from_slice(&[1, 5, 2]);
+    let other: SegTree = SegTree::from_slice(&[4, 3, 6]);
+    seg_tree.merge_with(&other, i32::max);
+    assert_eq!(seg_tree.to_vec(), vec![4, 5, 6]);
+    assert_eq!(seg_tree.total(), 15);
+
+    // differently shaped trees still pair up leaf by leaf
+    let mut uneven = SegTree::concat(SegTree::from_slice(&[1]), SegTree::new_with(1, 3, 2));
+    uneven.merge_with(&other, |a, b| a * b);
+    assert_eq!(uneven.to_vec(), vec![4, 6, 12]);
+}
+
+#[test]
+#[should_panic(expected = "different ranges")]
+fn test_merge_with_mismatch()
+{
+    let mut a: SegTree = SegTree::new(0, 4);
+    a.merge_with(&SegTree::new(0, 5), i32::max);
+}
+
+#[test]
+fn test_level_order()
+{
+    let seg_tree: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    let nodes: Vec<_> = seg_tree.level_order().collect();
+    assert_eq!(
+        nodes,
+        vec![
+            ((0, 4), 10),
+            ((0, 2), 3),
+            ((2, 4), 7),
+            ((0, 1), 1),
+            ((1, 2), 2),
+            ((2, 3), 3),
+            ((3, 4), 4),
+        ]
+    );
+    let uneven: SegTree = SegTree::new(0, 5);
+    assert_eq!(uneven.level_order().count(), uneven.node_count());
+}
+
+#[test]
+fn test_offline_mode()
+{
+    let mut state = 37;
+    for n in [1, 2, 17, 64, 101]
+    {
+        let data: Vec<i32> = (0..n)
+            .map(|_| (next_rand(&mut state) % 6) as i32 - 3)
+            .collect();
+        let mut queries = Vec::new();
+        for l in 0..n
+        {
+            for r in l + 1..=n
+            {
+                queries.push((l, r));
+            }
+        }
+        let expected: Vec<i32> = queries
+            .iter()
+            .map(|&(l, r)| {
+                let count = |v: i32| data[l..r].iter().filter(|&&x| x == v).count();
+                // the smallest value among those with the highest count
+                let best = data[l..r].iter().map(|&v| count(v)).max().unwrap();
+                data[l..r]
+                    .iter()
+                    .copied()
+                    .filter(|&v| count(v) == best)
+                    .min()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(offline_mode(&data, &queries), expected);
+    }
+}
+
+#[test]
+fn test_validate()
+{
+    let seg_tree: SegTree = (0..8).collect();
+    assert_eq!(seg_tree.validate(), Ok(()));
+    assert_eq!(
+        SegTree::concat(seg_tree.clone(), SegTree::new(8, 11)).validate(),
+        Ok(())
+    );
+
+    let left = seg_tree.left_child().unwrap();
+    left.borrow_mut().set_val_unchecked(100);
+    assert_eq!(
+        seg_tree.validate(),
+        Err(String::from(
+            "node [0, 8) holds 28, but its children combine to 122"
+        ))
+    );
+
+    let fixed: SegTree = (0..8).collect();
+    let left = fixed.left_child().unwrap();
+    left.borrow()
+        .left_child()
+        .unwrap()
+        .borrow_mut()
+        .set_val_unchecked(-1);
+    assert_eq!(
+        fixed.validate(),
+        Err(String::from(
+            "node [0, 4) holds 6, but its children combine to 4"
+        ))
+    );
+}
+
+#[test]
+fn test_bytes_round_trip()
+{
+    let mut state = 41;
+    let data: Vec<i32> = (0..1000)
+        .map(|_| (next_rand(&mut state) % 2001) as i32 - 1000)
+        .collect();
+    let mut seg_tree: SegTree = SegTree::from_slice(&data);
+    seg_tree.shift(3);
+    let bytes = seg_tree.to_bytes();
+    let loaded = SegTree::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.get_range(), (3, 1003));
+    for _ in 0..100
+    {
+        let a = (next_rand(&mut state) % 1001) as usize + 3;
+        let b = (next_rand(&mut state) % 1001) as usize + 3;
+        let (l, r) = (a.min(b), a.max(b));
+        assert_eq!(loaded.ask(l, r), seg_tree.ask(l, r));
+    }
+
+    let empty: SegTree = SegTree::new(4, 4);
+    assert!(SegTree::from_bytes(&empty.to_bytes()).unwrap().is_empty());
+}
+
+#[test]
+fn test_bytes_malformed()
+{
+    let bytes = SegTree::from_slice(&[1, 2, 3]).to_bytes();
+    for len in 0..bytes.len()
+    {
+        assert_eq!(
+            SegTree::from_bytes(&bytes[..len]).err(),
+            Some(SegError::InvalidBytes)
+        );
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        SegTree::from_bytes(&trailing).err(),
+        Some(SegError::InvalidBytes)
+    );
+    let mut reversed = bytes;
+    reversed[..8].copy_from_slice(&5u64.to_le_bytes());
+    assert_eq!(
+        SegTree::from_bytes(&reversed).err(),
+        Some(SegError::InvalidBytes)
+    );
+}
+
+#[test]
+fn test_drain_range()
+{
+    let mut seg_tree: SegTree = (0..9).collect();
+    assert_eq!(seg_tree.drain_range(2, 5), 2 + 3 + 4);
+    assert_eq!(seg_tree.to_vec(), vec![0, 1, 0, 0, 0, 5, 6, 7, 8]);
+    assert_eq!(seg_tree.total(), 36 - 9);
+    assert_eq!(seg_tree.drain_range(4, 4), 0);
+    assert_eq!(seg_tree.validate(), Ok(()));
+
+    let text = "segment";
+    let chars: Vec<String> = text.chars().map(String::from).collect();
+    let mut concat_tree = SegTree::<ConcatMonoid>::from_slice(&chars);
+    assert_eq!(concat_tree.drain_range(1, 6), "egmen");
+    assert_eq!(concat_tree.total(), "st");
+}
+
+// the `RefCell`s are never mutated through `&SegTree`, so trees make sound
+// keys
+#[allow(clippy::mutable_key_type)]
+#[test]
+fn test_hash()
+{
+    let mut set = std::collections::HashSet::new();
+    let built: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    let mut revised: SegTree = SegTree::new(0, 4);
+    for (i, v) in [1, 2, 3, 4].into_iter().enumerate()
+    {
+        revised.revise(i, v);
+    }
+    let mut joined = SegTree::concat(SegTree::from_slice(&[1]), SegTree::new_with(1, 4, 0));
+    joined.revise_many(&[(1, 2), (2, 3), (3, 4)]);
+    assert!(set.insert(built));
+    assert!(!set.insert(revised));
+    assert!(!set.insert(joined));
+    let mut shifted: SegTree = SegTree::from_slice(&[1, 2, 3, 4]);
+    shifted.shift(1);
+    assert!(set.insert(shifted));
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_ask_many()
+{
+    let mut state = 43;
+    let data: Vec<i32> = (0..100)
+        .map(|_| (next_rand(&mut state) % 100) as i32)
+        .collect();
+    let seg_tree: SegTree = SegTree::from_slice(&data);
+    let queries: Vec<(usize, usize)> = (0..1000)
+        .map(|_| {
+            let a = (next_rand(&mut state) % 101) as usize;
+            let b = (next_rand(&mut state) % 101) as usize;
+            (a.min(b), a.max(b))
+        })
+        .collect();
+    let expected: Vec<i32> = queries.iter().map(|&(l, r)| seg_tree.ask(l, r)).collect();
+    assert_eq!(seg_tree.ask_many(&queries), expected);
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range: [3, 11)")]
+fn test_ask_many_invalid()
+{
+    let seg_tree: SegTree = SegTree::new(0, 10);
+    let _ = seg_tree.ask_many(&[(0, 1), (3, 11)]);
+}
+
+#[test]
+fn test_toggle()
+{
+    let mut bits: SegTree = SegTree::from_slice(&[1, 0, 1, 0, 0, 1]);
+    let before = bits.total();
+    bits.toggle(3);
+    assert_eq!(bits.total(), before + 1);
+    assert_eq!(bits.kth_one(2), 2);
+    assert_eq!(bits.kth_one(3), 3);
+    bits.toggle(3);
+    assert_eq!(bits.total(), before);
+    bits.toggle(0);
+    assert_eq!(bits.ask(0, 3), 1);
+
+    let mut wide = SegTree::<SumMonoid<u64>>::new(0, 4);
+    wide.toggle(1);
+    assert_eq!(wide.total(), 1);
+}
+
+#[test]
+fn test_fn_seg_tree()
+{
+    let mut fn_tree = FnSegTree::with_combine(0, 5, i32::MAX, |a, b| a.min(b));
+    let mut min_tree = SegTree::new_min(0, 5);
+    for (i, v) in [5, 3, 8, 1, 6].into_iter().enumerate()
+    {
+        fn_tree.revise(i, v);
+        min_tree.revise(i, v);
+    }
+    for l in 0..5
+    {
+        for r in l + 1..=5
+        {
+            assert_eq!(fn_tree.ask(l, r), min_tree.ask(l, r));
+        }
+    }
+
+    // the closure may capture state and need not be commutative
+    let separator = String::from("-");
+    let mut joined = FnSegTree::with_combine(2, 5, String::new(), move |a: String, b: String| {
+        if a.is_empty() || b.is_empty()
+        {
+            a + &b
+        }
+        else
+        {
+            a + &separator + &b
+        }
+    });
+    for (i, s) in ["a", "b", "c"].into_iter().enumerate()
+    {
+        joined.revise(i + 2, s.to_string());
+    }
+    assert_eq!(joined.ask(2, 5), "a-b-c");
+    assert_eq!(
+        joined.try_ask(1, 5),
+        Err(SegError::InvalidRange { l: 1, r: 5 })
+    );
+}
+
+#[test]
+fn test_ask_or_identity()
+{
+    let seg_tree = SegTree::new_min(0, 4);
+    assert_eq!(seg_tree.ask_or_identity(2, 2), i32::MAX);
+    assert_eq!(seg_tree.ask_or_identity(3, 1), i32::MAX);
+    let sums: SegTree = (0..10).collect();
+    assert_eq!(sums.ask_or_identity(0, 10), sums.ask(0, 10));
+}
+
+#[test]
+#[should_panic(expected = "Invalid query range: [11, 3)")]
+fn test_ask_or_identity_out_of_bounds()
+{
+    let seg_tree: SegTree = SegTree::new(0, 10);
+    let _ = seg_tree.ask_or_identity(11, 3);
+}
+
+#[test]
+fn test_persistent_snapshot_restore()
+{
+    let mut seg_tree: PersistentSegTree = PersistentSegTree::from_slice(&[1, 2, 3, 4, 5]);
+    let saved = seg_tree.snapshot();
+    for i in 0..5
+    {
+        seg_tree = seg_tree.revise(i, 10 * i as i32);
+    }
+    let later = seg_tree.snapshot();
+    assert_eq!(seg_tree.ask(0, 5), 100);
+
+    seg_tree.restore(saved.clone());
+    for l in 0..5
+    {
+        for r in l + 1..=5
+        {
+            assert_eq!(seg_tree.ask(l, r), (l as i32 + 1..=r as i32).sum::<i32>());
+        }
+    }
+    seg_tree.restore(later);
+    assert_eq!(seg_tree.ask(1, 3), 30);
+    seg_tree.restore(saved);
+    assert_eq!(seg_tree.ask(0, 5), 15);
+}
+
+#[test]
+fn test_sparse_absent_subtrees_are_identity()
+{
+    let mut gcd_tree = SparseSegTree::<GcdMonoid>::new(0, 1 << 20);
+    gcd_tree.revise(5, 12);
+    gcd_tree.revise(1000, 18);
+    assert_eq!(gcd_tree.ask(0, 1 << 20), 6);
+    assert_eq!(gcd_tree.ask(0, 100), 12);
+    assert_eq!(gcd_tree.ask(100, 200), 0);
+    gcd_tree.revise(5, 0);
+    assert_eq!(gcd_tree.ask(0, 1 << 20), 18);
+
+    // a literal `0` fallback would turn every min into 0 here
+    let mut min_tree = SparseSegTree::<MinMonoid>::new(0, 1 << 20);
+    min_tree.revise(7, 3);
+    assert_eq!(min_tree.ask(0, 1 << 20), 3);
+    assert_eq!(min_tree.ask(8, 1 << 20), i32::MAX);
+}
+
+#[test]
+fn test_try_new()
+{
+    match SegTree::<SumMonoid>::try_new(10, 0)
+    {
+        Err(SegError::InvalidRange { l, r }) => assert_eq!((l, r), (10, 0)),
+        _ => panic!("expected an invalid range"),
+    }
+    let seg_tree: SegTree = SegTree::try_new(3, 9).unwrap();
+    assert_eq!(seg_tree.get_range(), (3, 9));
+    // an empty range is not an error, as `new(l, l)` builds an empty tree
+    assert!(SegTree::<SumMonoid>::try_new(3, 3).is_ok());
+    assert!(SegTree::<SumMonoid>::try_new(4, 4).unwrap().is_empty());
+}
+
+#[test]
+fn test_beats_chmin_edge_values()
+{
+    let mut seg_tree = BeatsSegTree::from_slice(&[5]);
+    seg_tree.range_chmin(0, 1, i32::MIN);
+    assert_eq!(seg_tree.ask(0, 1), i32::MIN);
+
+    let mut seg_tree = BeatsSegTree::from_slice(&[5, 3]);
+    seg_tree.range_chmin(0, 1, i32::MIN);
+    assert_eq!(seg_tree.ask(0, 2), i32::MIN + 3);
+    assert_eq!(seg_tree.ask_max(0, 1), i32::MIN);
+    assert_eq!(seg_tree.ask_max(0, 2), 3);
+
+    // `x` equal to an existing value only lowers the larger ones
+    let mut seg_tree = BeatsSegTree::from_slice(&[5, 3, 4, 3, 1]);
+    seg_tree.range_chmin(0, 5, 3);
+    assert_eq!(seg_tree.ask(0, 5), 3 + 3 + 3 + 3 + 1);
+    assert_eq!(seg_tree.ask_max(0, 5), 3);
+    seg_tree.range_chmin(1, 4, 3);
+    assert_eq!(seg_tree.ask(0, 5), 13);
+}
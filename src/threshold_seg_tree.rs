@@ -0,0 +1,87 @@
+use super::seg_tree::{SegTree, SumMonoid};
+
+/// Counts how many elements of a range exceed a threshold fixed at
+/// construction.
+///
+/// Only the count is kept: each leaf of the inner sum tree holds `1` if its
+/// value is above the threshold and `0` otherwise, and `revise` flips it as
+/// a value crosses the threshold.
+pub struct ThresholdSegTree
+{
+    threshold: i32,
+    counts: SegTree<SumMonoid<usize>>,
+}
+
+impl ThresholdSegTree
+{
+    /// Creates a tree over `[l, r)` counting values above `threshold`, every
+    /// value starting at `threshold`, so no element is counted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new(l: usize, r: usize, threshold: i32) -> ThresholdSegTree
+    {
+        ThresholdSegTree {
+            threshold,
+            counts: SegTree::new(l, r),
+        }
+    }
+    /// Creates a tree over `[0, data.len())` holding `data` and counting
+    /// values above `threshold`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::ThresholdSegTree;
+    /// let seg_tree = ThresholdSegTree::from_slice(&[3, -1, 0, 7], 0);
+    /// assert_eq!(seg_tree.count_above(0, 4), 2);
+    /// ```
+    pub fn from_slice(data: &[i32], threshold: i32) -> ThresholdSegTree
+    {
+        ThresholdSegTree {
+            threshold,
+            counts: data
+                .iter()
+                .map(|&value| (value > threshold) as usize)
+                .collect(),
+        }
+    }
+    /// Updates the value at a specific index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: i32)
+    {
+        self.counts
+            .revise(target_pos, (value > self.threshold) as usize);
+    }
+    /// Counts the values in `[l, r)` that are strictly greater than the
+    /// threshold.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn count_above(&self, l: usize, r: usize) -> usize
+    {
+        self.counts.ask(l, r)
+    }
+    /// Returns the threshold the tree was built with.
+    #[must_use]
+    pub fn threshold(&self) -> i32
+    {
+        self.threshold
+    }
+
+    #[must_use]
+    pub fn get_range(&self) -> (usize, usize)
+    {
+        self.counts.get_range()
+    }
+}
@@ -0,0 +1,124 @@
+use alloc::collections::VecDeque;
+
+use super::seg_tree::{Monoid, SegTree, SumMonoid};
+
+/// A [`SegTree`] that remembers its most recent `revise` calls so that they
+/// can be undone one at a time.
+///
+/// Each `revise` logs the index and the value it overwrote. Once the log
+/// holds `undo_limit` entries, the oldest one is dropped.
+pub struct UndoSegTree<M: Monoid = SumMonoid>
+{
+    tree: SegTree<M>,
+    history: VecDeque<(usize, M::Item)>,
+    undo_limit: usize,
+}
+
+impl<M: Monoid> UndoSegTree<M>
+{
+    /// The number of `revise` calls remembered by a new tree.
+    pub const DEFAULT_UNDO_LIMIT: usize = 100;
+
+    /// Creates a new tree over `[l, r)` with an empty history.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `l > r`, as this would create an invalid range.
+    pub fn new(l: usize, r: usize) -> UndoSegTree<M>
+    {
+        Self::from_tree(SegTree::new(l, r))
+    }
+    /// Creates a tree over `[0, data.len())` holding `data`, with an empty
+    /// history.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    pub fn from_slice(data: &[M::Item]) -> UndoSegTree<M>
+    {
+        Self::from_tree(SegTree::from_slice(data))
+    }
+    /// Wraps an existing tree, with an empty history.
+    pub fn from_tree(tree: SegTree<M>) -> UndoSegTree<M>
+    {
+        UndoSegTree {
+            tree,
+            history: VecDeque::new(),
+            undo_limit: Self::DEFAULT_UNDO_LIMIT,
+        }
+    }
+    /// Updates the value at a specific index, remembering the old value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the target index is out of range.
+    pub fn revise(&mut self, target_pos: usize, value: M::Item)
+    {
+        let old = self.tree.replace(target_pos, value);
+        if self.undo_limit == 0
+        {
+            return;
+        }
+        if self.history.len() == self.undo_limit
+        {
+            self.history.pop_front();
+        }
+        self.history.push_back((target_pos, old));
+    }
+    /// Reverts the most recent remembered `revise`, returning `false` if
+    /// there is nothing left to undo.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use seg_tree::UndoSegTree;
+    /// let mut seg_tree: UndoSegTree = UndoSegTree::new(0, 10);
+    /// seg_tree.revise(3, 1);
+    /// seg_tree.revise(3, 2);
+    /// assert!(seg_tree.undo());
+    /// assert_eq!(seg_tree.tree().get(3), 1);
+    /// ```
+    pub fn undo(&mut self) -> bool
+    {
+        match self.history.pop_back()
+        {
+            Some((pos, old)) =>
+            {
+                self.tree.revise(pos, old);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Sets how many `revise` calls are remembered, forgetting the oldest
+    /// ones if more are already logged.
+    pub fn set_undo_limit(&mut self, limit: usize)
+    {
+        self.undo_limit = limit;
+        while self.history.len() > limit
+        {
+            self.history.pop_front();
+        }
+    }
+    /// Returns the number of `revise` calls that can currently be undone.
+    #[must_use]
+    pub fn undo_len(&self) -> usize
+    {
+        self.history.len()
+    }
+    /// Queries the combined value of the specified range `[l, r)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the query range is invalid.
+    #[must_use]
+    pub fn ask(&self, l: usize, r: usize) -> M::Item
+    {
+        self.tree.ask(l, r)
+    }
+    /// Returns the underlying tree for read-only queries.
+    pub fn tree(&self) -> &SegTree<M>
+    {
+        &self.tree
+    }
+}